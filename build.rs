@@ -0,0 +1,47 @@
+//! Packs the words of every embedded list in lists/ into a single string, with
+//! a table of where each word starts, so that they take neither a pointer per
+//! word in the binary nor any work at runtime.
+
+use std::env;
+use std::fs;
+use std::path::Path;
+
+/// The embedded lists, as named in lists/. Their lines must be in the order
+/// of their dice rolls, as in the EFF's files.
+const LISTS: &[&str] = &["eff_large_wordlist", "eff_short_wordlist_1"];
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+
+    let out_dir = env::var_os("OUT_DIR").expect("cargo sets OUT_DIR");
+    for list in LISTS {
+        let path = format!("lists/{list}.txt");
+        println!("cargo:rerun-if-changed={path}");
+
+        let text = fs::read_to_string(&path).unwrap_or_else(|err| panic!("{path}: {err}"));
+        let out = Path::new(&out_dir).join(format!("{list}.rs"));
+        fs::write(&out, pack(&text)).unwrap_or_else(|err| panic!("{}: {err}", out.display()));
+    }
+}
+
+/// A `PackedWords` expression holding the words of the dice-indexed list
+/// `text`, in order, and the size of the largest of them.
+fn pack(text: &str) -> String {
+    let mut data = String::new();
+    let mut offsets = vec![String::from("0")];
+    let mut max_size = 0;
+    for line in text.lines().filter(|line| !line.trim().is_empty()) {
+        let word = match line.split_whitespace().collect::<Vec<_>>()[..] {
+            [_, word] => word,
+            _ => panic!("expected dice rolls and a word, got {line:?}"),
+        };
+        data.push_str(word);
+        max_size = max_size.max(word.len());
+        offsets.push(data.len().to_string());
+    }
+
+    format!(
+        "PackedWords::new({data:?}, &[{}], {max_size})",
+        offsets.join(", ")
+    )
+}
@@ -0,0 +1,58 @@
+/// How the words of a passphrase are capitalized.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Case {
+    /// all words in lowercase
+    #[default]
+    Lower,
+    /// Every Word Capitalized
+    Title,
+    /// firstWordLowercaseTheRestCapitalized
+    Camel,
+    /// ALL WORDS IN UPPERCASE
+    Upper,
+    /// all words in lowercase, except for One randomly chosen word that is
+    /// capitalized
+    RandomWord,
+}
+
+impl Case {
+    /// Whether applying this case involves chance.
+    pub fn is_random(self) -> bool {
+        self == Case::RandomWord
+    }
+
+    /// Every way a word may be capitalized in a passphrase.
+    pub(crate) fn forms(self, word: &str) -> Vec<String> {
+        match self {
+            Case::Lower => vec![word.to_lowercase()],
+            Case::Title => vec![capitalize(word)],
+            Case::Upper => vec![word.to_uppercase()],
+            Case::Camel | Case::RandomWord => vec![word.to_lowercase(), capitalize(word)],
+        }
+    }
+
+    /// Apply this case to the word at `position` in a passphrase, `chosen`
+    /// being the position of the randomly chosen word, if any.
+    pub(crate) fn apply(self, word: &str, position: usize, chosen: Option<usize>) -> String {
+        match self {
+            Case::Lower => word.to_lowercase(),
+            Case::Title => capitalize(word),
+            Case::Camel if position == 0 => word.to_lowercase(),
+            Case::Camel => capitalize(word),
+            Case::Upper => word.to_uppercase(),
+            Case::RandomWord if chosen == Some(position) => capitalize(word),
+            Case::RandomWord => word.to_lowercase(),
+        }
+    }
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first
+            .to_uppercase()
+            .chain(chars.flat_map(char::to_lowercase))
+            .collect(),
+        None => String::new(),
+    }
+}
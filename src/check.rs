@@ -0,0 +1,36 @@
+use crate::Entropy;
+
+/// The outcome of checking an existing passphrase against a wordlist, see
+/// [`Generator::check`](crate::Generator::check).
+#[derive(Debug, Clone)]
+pub struct Check {
+    matched: Vec<String>,
+    unmatched: Vec<String>,
+    entropy: Entropy,
+}
+
+impl Check {
+    pub(crate) fn new(matched: Vec<String>, unmatched: Vec<String>, entropy: Entropy) -> Self {
+        Self {
+            matched,
+            unmatched,
+            entropy,
+        }
+    }
+
+    /// The words that are part of the wordlist.
+    pub fn matched(&self) -> &[String] {
+        &self.matched
+    }
+
+    /// The words that aren't part of the wordlist.
+    pub fn unmatched(&self) -> &[String] {
+        &self.unmatched
+    }
+
+    /// The entropy of the passphrase, assuming its matched words were picked
+    /// at random. Unmatched words don't count towards it.
+    pub fn entropy(&self) -> &Entropy {
+        &self.entropy
+    }
+}
@@ -0,0 +1,372 @@
+//! Building diceware wordlists from text corpora.
+
+use crate::lint::bounded_levenshtein;
+use crate::wordlist::read_file;
+use crate::{Error, Wordlist};
+use std::collections::{HashMap, HashSet};
+use std::ops::RangeInclusive;
+use std::path::Path;
+
+/// How often each word occurs in a body of text.
+#[derive(Debug, Clone, Default)]
+pub struct Corpus {
+    counts: HashMap<String, u64>,
+}
+
+impl Corpus {
+    /// An empty corpus.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count the words of `text`, that is, its runs of letters, in lowercase.
+    pub fn add(&mut self, text: &str) {
+        for word in text.split(|c: char| !c.is_alphabetic()) {
+            if !word.is_empty() {
+                *self.counts.entry(word.to_lowercase()).or_insert(0) += 1;
+            }
+        }
+    }
+
+    /// Count the words of the text file at `path`.
+    pub fn add_file(&mut self, path: impl AsRef<Path>) -> Result<(), Error> {
+        self.add(&read_file(path.as_ref())?);
+        Ok(())
+    }
+
+    /// The number of distinct words counted.
+    pub fn len(&self) -> usize {
+        self.counts.len()
+    }
+
+    /// Whether no words have been counted yet.
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    /// How often `word` occurs.
+    pub fn count(&self, word: &str) -> u64 {
+        self.counts.get(word).copied().unwrap_or(0)
+    }
+
+    /// All words, the most frequent first and equally frequent ones in
+    /// alphabetical order.
+    fn by_frequency(&self) -> Vec<(&str, u64)> {
+        let mut words: Vec<(&str, u64)> = self
+            .counts
+            .iter()
+            .map(|(word, count)| (word.as_str(), *count))
+            .collect();
+        words.sort_unstable_by(|(a, a_count), (b, b_count)| {
+            b_count.cmp(a_count).then_with(|| a.cmp(b))
+        });
+
+        words
+    }
+}
+
+/// Picks the most frequent words of a [`Corpus`] that pass a set of filters
+/// for a wordlist usable with dice.
+#[derive(Debug, Clone)]
+pub struct ListBuilder {
+    lengths: RangeInclusive<usize>,
+    charset: Option<HashSet<char>>,
+    blocklist: HashSet<String>,
+    min_edit_distance: usize,
+    num_dice: Option<u32>,
+}
+
+impl ListBuilder {
+    /// A builder for lists of words between 3 and 9 characters long, that
+    /// differ from each other in at least one character.
+    pub fn new() -> Self {
+        Self {
+            lengths: 3..=9,
+            charset: None,
+            blocklist: HashSet::new(),
+            min_edit_distance: 1,
+            num_dice: None,
+        }
+    }
+
+    /// Only pick words whose length, in characters, is within `lengths`.
+    pub fn lengths(mut self, lengths: RangeInclusive<usize>) -> Self {
+        self.lengths = lengths;
+        self
+    }
+
+    /// Only pick words made up entirely of characters in `charset`.
+    pub fn charset(mut self, charset: &str) -> Self {
+        self.charset = Some(charset.chars().collect());
+        self
+    }
+
+    /// Never pick any of `words`, e.g. profanity.
+    pub fn blocklist<I, S>(mut self, words: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.blocklist
+            .extend(words.into_iter().map(|word| word.as_ref().to_lowercase()));
+        self
+    }
+
+    /// Never pick any of the words in the file at `path`, separated by
+    /// whitespace.
+    pub fn blocklist_file(self, path: impl AsRef<Path>) -> Result<Self, Error> {
+        let text = read_file(path.as_ref())?;
+        Ok(self.blocklist(text.split_whitespace()))
+    }
+
+    /// Only pick words at least `distance` single character edits away from
+    /// every word picked before.
+    pub fn min_edit_distance(mut self, distance: usize) -> Self {
+        self.min_edit_distance = distance.max(1);
+        self
+    }
+
+    /// Pick 6^`num_dice` words. By default, as many dice as the corpus has
+    /// words for are used.
+    pub fn num_dice(mut self, num_dice: u32) -> Self {
+        self.num_dice = Some(num_dice);
+        self
+    }
+
+    /// Build a list from the most frequent words of `corpus` that pass the
+    /// filters, in alphabetical order.
+    pub fn build(&self, corpus: &Corpus) -> Result<Wordlist, Error> {
+        let target = match self.num_dice {
+            Some(num_dice) => 6usize.checked_pow(num_dice).ok_or_else(|| {
+                Error::InvalidWordlist(format!("6^{num_dice} words are too many"))
+            })?,
+            None => usize::MAX,
+        };
+
+        // Words picked so far, by length in characters. Words whose lengths
+        // differ by at least the minimum edit distance needn't be compared.
+        let mut picked: Vec<&str> = Vec::new();
+        let mut by_length: Vec<Vec<Vec<char>>> = Vec::new();
+        for (word, _) in corpus.by_frequency() {
+            if picked.len() == target {
+                break;
+            }
+            if !self.accepts(word) {
+                continue;
+            }
+
+            let chars: Vec<char> = word.chars().collect();
+            let len = chars.len();
+            if by_length.len() <= len {
+                by_length.resize(len + 1, Vec::new());
+            }
+
+            let reach = self.min_edit_distance - 1;
+            let nearby = len.saturating_sub(reach)..=(len + reach).min(by_length.len() - 1);
+            let too_close = self.min_edit_distance > 1
+                && by_length[nearby].iter().flatten().any(|other| {
+                    bounded_levenshtein(other, &chars, self.min_edit_distance)
+                        < self.min_edit_distance
+                });
+            if !too_close {
+                picked.push(word);
+                by_length[len].push(chars);
+            }
+        }
+
+        let len = match self.num_dice {
+            Some(_) if picked.len() < target => {
+                return Err(Error::InvalidWordlist(format!(
+                    "only {} words pass the filters, but {target} are needed",
+                    picked.len()
+                )));
+            }
+            Some(_) => target,
+            None => largest_power_of_six(picked.len()).ok_or_else(|| {
+                Error::InvalidWordlist(format!(
+                    "only {} words pass the filters, but at least 6 are needed",
+                    picked.len()
+                ))
+            })?,
+        };
+
+        // The most frequent words make the cut.
+        picked.truncate(len);
+        picked.sort_unstable();
+        Wordlist::from_words(picked)
+    }
+
+    fn accepts(&self, word: &str) -> bool {
+        self.lengths.contains(&word.chars().count())
+            && self
+                .charset
+                .as_ref()
+                .is_none_or(|charset| word.chars().all(|c| charset.contains(&c)))
+            && !self.blocklist.contains(word)
+    }
+}
+
+impl Default for ListBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The largest power of six no greater than `n`, other than 1.
+fn largest_power_of_six(n: usize) -> Option<usize> {
+    let mut power = 6usize;
+    if n < power {
+        return None;
+    }
+
+    while let Some(next) = power.checked_mul(6).filter(|next| *next <= n) {
+        power = next;
+    }
+
+    Some(power)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A corpus in which each word occurs as often as given.
+    fn corpus(counts: &[(&str, usize)]) -> Corpus {
+        let mut corpus = Corpus::new();
+        for (word, count) in counts {
+            corpus.add(&format!("{word} ").repeat(*count));
+        }
+        corpus
+    }
+
+    fn words(wordlist: &Wordlist) -> Vec<&str> {
+        wordlist.iter().collect()
+    }
+
+    const SEVEN: [(&str, usize); 7] = [
+        ("apple", 7),
+        ("banana", 6),
+        ("cherry", 5),
+        ("grape", 4),
+        ("lemon", 3),
+        ("mango", 2),
+        ("kiwi", 1),
+    ];
+
+    #[test]
+    fn counts_runs_of_letters_in_lowercase() {
+        let mut corpus = Corpus::new();
+        corpus.add("The cat's hat, the CAT!");
+        assert_eq!(corpus.len(), 4);
+        assert_eq!(corpus.count("the"), 2);
+        assert_eq!(corpus.count("cat"), 2);
+        assert_eq!(corpus.count("s"), 1);
+        assert_eq!(corpus.count("dog"), 0);
+    }
+
+    #[test]
+    fn orders_words_by_frequency_then_alphabetically() {
+        let corpus = corpus(&[("bee", 2), ("cow", 1), ("ant", 2)]);
+        assert_eq!(corpus.by_frequency(), [("ant", 2), ("bee", 2), ("cow", 1)]);
+    }
+
+    #[test]
+    fn picks_the_most_frequent_words() {
+        let wordlist = ListBuilder::new().build(&corpus(&SEVEN)).unwrap();
+        assert_eq!(
+            words(&wordlist),
+            ["apple", "banana", "cherry", "grape", "lemon", "mango"]
+        );
+
+        // Of equally frequent words, the alphabetically first make the cut.
+        let mut tied = SEVEN;
+        tied[6] = ("kiwi", 2);
+        let wordlist = ListBuilder::new().build(&corpus(&tied)).unwrap();
+        assert!(words(&wordlist).contains(&"kiwi"));
+        assert!(!words(&wordlist).contains(&"mango"));
+    }
+
+    #[test]
+    fn filters_by_length_charset_and_blocklist() {
+        let err = ListBuilder::new()
+            .lengths(6..=6)
+            .build(&corpus(&SEVEN))
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("only 2 words pass the filters, but at least 6 are needed"));
+
+        let corpus = corpus(&[SEVEN.as_slice(), &[("date", 1), ("fig", 1)]].concat());
+        let wordlist = ListBuilder::new().lengths(3..=5).build(&corpus).unwrap();
+        assert_eq!(
+            words(&wordlist),
+            ["apple", "date", "fig", "grape", "lemon", "mango"]
+        );
+
+        let wordlist = ListBuilder::new()
+            .blocklist(["Apple", "BANANA"])
+            .build(&corpus)
+            .unwrap();
+        assert!(!words(&wordlist).contains(&"apple"));
+        assert!(!words(&wordlist).contains(&"banana"));
+
+        let wordlist = ListBuilder::new()
+            .charset("abcdefgilmnoprwy")
+            .build(&corpus)
+            .unwrap();
+        assert!(!words(&wordlist).contains(&"cherry"));
+        assert!(!words(&wordlist).contains(&"kiwi"));
+    }
+
+    #[test]
+    fn keeps_words_apart_by_edit_distance() {
+        let mut counts = vec![("cat", 9), ("bat", 8), ("cart", 7), ("catty", 6)];
+        counts.extend(SEVEN);
+        let corpus = corpus(&counts);
+
+        // "bat" and "cart" are a single edit away from "cat", "catty" two.
+        let wordlist = ListBuilder::new()
+            .min_edit_distance(2)
+            .build(&corpus)
+            .unwrap();
+        let picked = words(&wordlist);
+        assert!(picked.contains(&"cat") && picked.contains(&"catty"));
+        assert!(!picked.contains(&"bat") && !picked.contains(&"cart"));
+
+        // Words two characters longer are still compared at a distance of 3.
+        let wordlist = ListBuilder::new()
+            .min_edit_distance(3)
+            .build(&corpus)
+            .unwrap();
+        assert!(!words(&wordlist).contains(&"catty"));
+    }
+
+    #[test]
+    fn fails_short_of_the_requested_dice() {
+        let err = ListBuilder::new()
+            .num_dice(2)
+            .build(&corpus(&SEVEN))
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("only 7 words pass the filters, but 36 are needed"));
+
+        let wordlist = ListBuilder::new()
+            .num_dice(1)
+            .build(&corpus(&SEVEN))
+            .unwrap();
+        assert_eq!(wordlist.len(), 6);
+    }
+
+    #[test]
+    fn finds_the_largest_power_of_six() {
+        assert_eq!(largest_power_of_six(0), None);
+        assert_eq!(largest_power_of_six(5), None);
+        assert_eq!(largest_power_of_six(6), Some(6));
+        assert_eq!(largest_power_of_six(35), Some(6));
+        assert_eq!(largest_power_of_six(36), Some(36));
+        assert_eq!(largest_power_of_six(7775), Some(1296));
+        assert_eq!(largest_power_of_six(7776), Some(7776));
+        assert!(largest_power_of_six(usize::MAX).is_some());
+    }
+}
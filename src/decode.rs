@@ -0,0 +1,250 @@
+//! Whether passphrases can be split back into the words they were made of.
+//!
+//! A passphrase `w1 d w2 d ... wn` followed by one more delimiter `d` is the
+//! concatenation of the codewords `w1 d`, `w2 d`, ..., `wn d`. Different
+//! choices of words always make for different passphrases exactly if the set
+//! of all codewords is uniquely decodable.
+
+use crate::delimiter::Delimiter;
+use crate::{Case, Wordlist};
+use std::collections::{HashMap, HashSet};
+
+/// Whether every concatenation of words from `code` can be split into words
+/// in only one way, as determined by the Sardinas–Patterson algorithm.
+pub fn is_uniquely_decodable<S: AsRef<str>>(code: &[S]) -> bool {
+    let code: Vec<&str> = code.iter().map(AsRef::as_ref).collect();
+    Code::new(&code).is_uniquely_decodable()
+}
+
+/// Which words of a list can be told apart in passphrases.
+#[derive(Debug, Clone)]
+pub(crate) struct Analysis {
+    pub(crate) uniquely_decodable: bool,
+    /// Indices of the words left out of a uniquely decodable subset, sorted.
+    pub(crate) ambiguous: Vec<usize>,
+}
+
+impl Analysis {
+    pub(crate) fn new(wordlist: &Wordlist, delimiter: &Delimiter, case: Case) -> Self {
+        let codewords: Vec<(usize, String)> = wordlist
+            .iter()
+            .enumerate()
+            .flat_map(|(index, word)| {
+                codewords(word, delimiter, case)
+                    .into_iter()
+                    .map(move |codeword| (index, codeword))
+            })
+            .collect();
+
+        let code: Vec<&str> = codewords.iter().map(|(_, c)| c.as_str()).collect();
+        let code = Code::new(&code);
+        if code.is_uniquely_decodable() {
+            return Self {
+                uniquely_decodable: true,
+                ambiguous: Vec::new(),
+            };
+        }
+
+        // Words sharing a codeword with an earlier word can't be told apart
+        // from it at all.
+        let mut owners: HashMap<&str, usize> = HashMap::new();
+        let duplicates: HashSet<usize> = codewords
+            .iter()
+            .filter(|(index, codeword)| *owners.entry(codeword).or_insert(*index) != *index)
+            .map(|(index, _)| *index)
+            .collect();
+
+        // A prefix-free code is always uniquely decodable. Get one by either
+        // dropping every word with a codeword that extends another codeword,
+        // or every word with a codeword that another codeword extends, and
+        // keep whichever loses fewer words.
+        let extending: HashSet<usize> = codewords
+            .iter()
+            .filter(|(_, codeword)| code.has_proper_prefix(codeword))
+            .map(|(index, _)| *index)
+            .chain(duplicates.iter().copied())
+            .collect();
+        let extended: HashSet<usize> = codewords
+            .iter()
+            .filter(|(_, codeword)| code.has_proper_extension(codeword))
+            .map(|(index, _)| *index)
+            .chain(duplicates.iter().copied())
+            .collect();
+
+        let mut ambiguous: Vec<usize> = if extending.len() <= extended.len() {
+            extending.into_iter().collect()
+        } else {
+            extended.into_iter().collect()
+        };
+        ambiguous.sort_unstable();
+
+        Self {
+            uniquely_decodable: false,
+            ambiguous,
+        }
+    }
+
+    pub(crate) fn is_ambiguous(&self, index: usize) -> bool {
+        self.ambiguous.binary_search(&index).is_ok()
+    }
+}
+
+/// The codewords a word turns into, one for every way it can be capitalized
+/// and every possible delimiter.
+fn codewords(word: &str, delimiter: &Delimiter, case: Case) -> Vec<String> {
+    let delimiters: Vec<String> = match delimiter {
+        Delimiter::Fixed(delimiter) => vec![delimiter.clone()],
+        Delimiter::Random(set) => set.iter().map(|c| String::from(*c)).collect(),
+    };
+
+    let mut codewords = Vec::new();
+    for form in case.forms(word) {
+        for delimiter in &delimiters {
+            codewords.push(format!("{form}{delimiter}"));
+        }
+    }
+    codewords.sort_unstable();
+    codewords.dedup();
+
+    codewords
+}
+
+struct Code<'a> {
+    words: HashSet<&'a str>,
+    sorted: Vec<&'a str>,
+}
+
+impl<'a> Code<'a> {
+    fn new(code: &[&'a str]) -> Self {
+        let mut sorted = code.to_vec();
+        sorted.sort_unstable();
+        Self {
+            words: code.iter().copied().collect(),
+            sorted,
+        }
+    }
+
+    /// Codewords that start with `prefix` without being equal to it.
+    fn extensions<'s>(&'s self, prefix: &'s str) -> impl Iterator<Item = &'a str> + 's {
+        let start = self.sorted.partition_point(|word| *word <= prefix);
+        self.sorted[start..]
+            .iter()
+            .copied()
+            .take_while(move |word| word.starts_with(prefix))
+    }
+
+    /// Codewords that `s` starts with, without being equal to them.
+    fn prefixes<'s>(&'s self, s: &'s str) -> impl Iterator<Item = &'s str> + 's {
+        s.char_indices()
+            .skip(1)
+            .map(move |(i, _)| &s[..i])
+            .filter(move |prefix| self.words.contains(prefix))
+    }
+
+    fn has_proper_prefix(&self, s: &str) -> bool {
+        self.prefixes(s).next().is_some()
+    }
+
+    fn has_proper_extension(&self, s: &str) -> bool {
+        self.extensions(s).next().is_some()
+    }
+
+    fn is_uniquely_decodable(&self) -> bool {
+        if self.words.len() != self.sorted.len() || self.words.contains("") {
+            return false;
+        }
+
+        // The dangling suffixes left over when one codeword is a prefix of
+        // another; the code is uniquely decodable unless following them
+        // ever leads to a codeword.
+        let mut seen: HashSet<String> = HashSet::new();
+        let mut frontier: Vec<String> = Vec::new();
+        for word in &self.sorted {
+            for extension in self.extensions(word) {
+                let suffix = &extension[word.len()..];
+                if seen.insert(String::from(suffix)) {
+                    frontier.push(String::from(suffix));
+                }
+            }
+        }
+
+        while let Some(suffix) = frontier.pop() {
+            if self.words.contains(suffix.as_str()) {
+                return false;
+            }
+
+            let prefixed = self.prefixes(&suffix).map(|prefix| &suffix[prefix.len()..]);
+            let extended = self
+                .extensions(&suffix)
+                .map(|extension| &extension[suffix.len()..]);
+            let next: Vec<String> = prefixed.chain(extended).map(String::from).collect();
+            for dangling in next {
+                if seen.insert(dangling.clone()) {
+                    frontier.push(dangling);
+                }
+            }
+        }
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn analyze(words: &str, delimiter: &str) -> Analysis {
+        let wordlist = Wordlist::parse(words).unwrap();
+        Analysis::new(
+            &wordlist,
+            &Delimiter::Fixed(String::from(delimiter)),
+            Case::Lower,
+        )
+    }
+
+    #[test]
+    fn detects_codes_that_are_not_uniquely_decodable() {
+        // "aba" is both "a" "ba" and "ab" "a".
+        assert!(!is_uniquely_decodable(&["a", "ab", "ba"]));
+        assert!(!is_uniquely_decodable(&["0", "01", "10"]));
+        assert!(!is_uniquely_decodable(&["a", "a"]));
+        assert!(!is_uniquely_decodable(&["a", ""]));
+    }
+
+    #[test]
+    fn accepts_uniquely_decodable_codes_that_are_not_prefix_free() {
+        // "ab" starts with "a", but what follows "a" never completes a split:
+        // "abc" is only "a" "bc".
+        assert!(is_uniquely_decodable(&["a", "ab", "bc"]));
+        assert!(is_uniquely_decodable(&["a", "ab", "bb"]));
+        assert!(is_uniquely_decodable(&["a", "b", "c"]));
+    }
+
+    #[test]
+    fn leaves_out_the_fewest_words() {
+        let analysis = analyze("a\nab\nba\n", "");
+        assert!(!analysis.uniquely_decodable);
+        assert_eq!(analysis.ambiguous, [1]);
+
+        // Dropping the one word others extend beats dropping the two that
+        // extend it.
+        let analysis = analyze("a\nab\nac\nb\n", "");
+        assert_eq!(analysis.ambiguous, [0]);
+    }
+
+    #[test]
+    fn finds_nothing_ambiguous_in_uniquely_decodable_lists() {
+        let analysis = analyze("a\nab\nbc\n", "");
+        assert!(analysis.uniquely_decodable);
+        assert!(analysis.ambiguous.is_empty());
+    }
+
+    #[test]
+    fn words_containing_the_delimiter_can_be_ambiguous() {
+        let analysis = analyze("drop\ndown\ndrop-down\n", "-");
+        assert!(!analysis.uniquely_decodable);
+        assert_eq!(analysis.ambiguous, [2]);
+
+        assert!(analyze("drop\ndown\ndrop-down\n", " ").uniquely_decodable);
+    }
+}
@@ -0,0 +1,58 @@
+use crate::source::uniform;
+use crate::{extras, Error};
+use rand::RngCore;
+
+/// What goes between the words of a passphrase.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Delimiter {
+    /// The same string between every two words.
+    Fixed(String),
+    /// A character drawn at random for every gap between two words.
+    Random(Vec<char>),
+}
+
+impl Delimiter {
+    /// A delimiter drawn from `set`, or none at all if `set` is empty.
+    pub(crate) fn random(set: &str) -> Self {
+        if set.is_empty() {
+            return Delimiter::Fixed(String::new());
+        }
+
+        Delimiter::Random(extras::charset(set))
+    }
+
+    pub(crate) fn is_random(&self) -> bool {
+        matches!(self, Delimiter::Random(_))
+    }
+
+    /// Size, in bytes, of the largest possible delimiter.
+    pub(crate) fn max_size(&self) -> usize {
+        match self {
+            Delimiter::Fixed(delimiter) => delimiter.len(),
+            Delimiter::Random(set) => set.iter().map(|c| c.len_utf8()).max().unwrap_or(0),
+        }
+    }
+
+    /// The number of different delimiters to choose from for every gap.
+    pub(crate) fn choices(&self) -> usize {
+        match self {
+            Delimiter::Fixed(_) => 1,
+            Delimiter::Random(set) => set.len(),
+        }
+    }
+
+    /// Every character that can be part of a delimiter.
+    pub(crate) fn chars(&self) -> Vec<char> {
+        match self {
+            Delimiter::Fixed(delimiter) => delimiter.chars().collect(),
+            Delimiter::Random(set) => set.clone(),
+        }
+    }
+
+    pub(crate) fn draw<R: RngCore>(&self, rng: &mut R) -> Result<String, Error> {
+        match self {
+            Delimiter::Fixed(delimiter) => Ok(delimiter.clone()),
+            Delimiter::Random(set) => Ok(String::from(set[uniform(rng, set.len())?])),
+        }
+    }
+}
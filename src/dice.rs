@@ -0,0 +1,368 @@
+//! Picking words with physical dice instead of a computer's RNG.
+//!
+//! Dice can be loaded, and people asked for rolls have been known to make
+//! them up. A [`Tally`] counts how often each face came up, and a
+//! chi-squared test tells when those counts are unlikely for a fair die. To
+//! test dice across sessions, each [`Session`] can be appended to a roll log.
+
+use crate::wordlist::read_file;
+use crate::Error;
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The number of faces of a die.
+pub const FACES: usize = 6;
+
+/// The chi-squared statistic above which a [`Tally`] is suspicious: the
+/// critical value for 5 degrees of freedom at a significance level of 1%.
+pub const CRITICAL_VALUE: f64 = 15.086;
+
+/// The fewest rolls a [`Tally`] is tested with, so that every face is
+/// expected to come up at least 5 times, as the chi-squared test needs.
+pub const MIN_ROLLS: u64 = 5 * FACES as u64;
+
+/// Parse a sequence of six-sided dice rolls, e.g. `"16325"` or `"1 6 3 2 5"`.
+///
+/// Whitespace and commas between rolls are ignored; anything other than the
+/// digits 1 through 6 is rejected.
+pub fn parse_rolls(s: &str) -> Result<Vec<u8>, Error> {
+    s.chars()
+        .filter(|c| !c.is_whitespace() && *c != ',')
+        .map(|c| match c {
+            '1'..='6' => Ok(c as u8 - b'0'),
+            _ => Err(Error::InvalidRolls(format!("'{c}' is not a roll of a d6"))),
+        })
+        .collect()
+}
+
+/// The number of rolls of a d6 needed to pick one of `len` items, if `len`
+/// is a power of six.
+pub(crate) fn dice_for(len: usize) -> Option<u32> {
+    let mut num_dice = 0;
+    let mut combinations = 1usize;
+    while combinations < len {
+        combinations = combinations.checked_mul(6)?;
+        num_dice += 1;
+    }
+
+    (combinations == len).then_some(num_dice)
+}
+
+/// Map `rolls` to an index, reading them as the digits of a base 6 number.
+pub(crate) fn index_for(rolls: &[u8]) -> usize {
+    rolls
+        .iter()
+        .fold(0, |index, roll| index * 6 + usize::from(roll - 1))
+}
+
+/// The rolls that map to `index`, the inverse of [`index_for`].
+pub(crate) fn rolls_for(mut index: usize, num_dice: u32) -> Vec<u8> {
+    let mut rolls = vec![1; num_dice as usize];
+    for roll in rolls.iter_mut().rev() {
+        *roll += (index % 6) as u8;
+        index /= 6;
+    }
+
+    rolls
+}
+
+/// Format rolls the way the EFF's lists do, e.g. `16325`.
+pub fn format_rolls(rolls: &[u8]) -> String {
+    rolls.iter().map(|roll| char::from(b'0' + roll)).collect()
+}
+
+/// How many times each face of a die came up.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Tally {
+    counts: [u64; FACES],
+}
+
+impl Tally {
+    /// A tally of no rolls at all.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count `rolls`, as returned by [`parse_rolls`].
+    ///
+    /// # Panics
+    ///
+    /// If a roll isn't between 1 and 6.
+    pub fn add(&mut self, rolls: &[u8]) {
+        for &roll in rolls {
+            assert!((1..=6).contains(&roll), "{roll} is not a roll of a d6");
+            self.counts[usize::from(roll - 1)] += 1;
+        }
+    }
+
+    /// Count every roll of `other` too.
+    pub fn merge(&mut self, other: &Tally) {
+        for (count, other) in self.counts.iter_mut().zip(other.counts) {
+            *count += other;
+        }
+    }
+
+    /// How many times each face came up, from 1 to 6.
+    pub fn counts(&self) -> [u64; FACES] {
+        self.counts
+    }
+
+    /// The number of rolls counted.
+    pub fn total(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    /// Pearson's chi-squared statistic of the counts against a fair die.
+    pub fn chi_squared(&self) -> f64 {
+        let total = self.total();
+        if total == 0 {
+            return 0.0;
+        }
+
+        let expected = total as f64 / FACES as f64;
+        self.counts
+            .iter()
+            .map(|&count| (count as f64 - expected).powi(2) / expected)
+            .sum()
+    }
+
+    /// Whether a fair die would give counts this uneven less than 1% of the
+    /// time. Never true for fewer than [`MIN_ROLLS`] rolls.
+    pub fn is_suspicious(&self) -> bool {
+        self.total() >= MIN_ROLLS && self.chi_squared() > CRITICAL_VALUE
+    }
+}
+
+/// A session of dice rolls, as kept in a roll log.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Session {
+    time: u64,
+    tally: Tally,
+    rolls: Option<Vec<u8>>,
+}
+
+impl Session {
+    /// A session of `rolls` happening now, only keeping how often each face
+    /// came up.
+    pub fn new(rolls: &[u8]) -> Self {
+        let mut tally = Tally::new();
+        tally.add(rolls);
+        let time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+
+        Self {
+            time,
+            tally,
+            rolls: None,
+        }
+    }
+
+    /// A session of `rolls` happening now, keeping the rolls themselves too.
+    ///
+    /// Logged rolls give away the passphrases they picked to anyone who reads
+    /// the log.
+    pub fn with_rolls(rolls: &[u8]) -> Self {
+        Self {
+            rolls: Some(rolls.to_vec()),
+            ..Self::new(rolls)
+        }
+    }
+
+    /// When the session happened, in seconds since the Unix epoch.
+    pub fn time(&self) -> u64 {
+        self.time
+    }
+
+    /// How many times each face came up.
+    pub fn tally(&self) -> &Tally {
+        &self.tally
+    }
+
+    /// The rolls themselves, in order, if they were kept.
+    pub fn rolls(&self) -> Option<&[u8]> {
+        self.rolls.as_deref()
+    }
+
+    /// Append this session to the log at `path`, creating it if needed.
+    ///
+    /// Every session is a line of its time, the counts per face and, if they
+    /// were kept, the rolls, separated by tabs, e.g.
+    /// `1760774400\t2 1 1 0 0 1\t16325`.
+    pub fn append_to_log(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+        let path = path.as_ref();
+        let with_path =
+            |err: io::Error| io::Error::new(err.kind(), format!("{}: {err}", path.display()));
+
+        let counts: Vec<String> = self.tally.counts.iter().map(u64::to_string).collect();
+        let mut line = format!("{}\t{}", self.time, counts.join(" "));
+        if let Some(rolls) = &self.rolls {
+            line.push('\t');
+            line.push_str(&format_rolls(rolls));
+        }
+
+        let mut log = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(with_path)?;
+        writeln!(log, "{line}").map_err(with_path)?;
+
+        Ok(())
+    }
+}
+
+/// Read the sessions logged to `path` by [`Session::append_to_log`], in the
+/// order they were logged. A log that doesn't exist yet holds no sessions.
+pub fn read_log(path: impl AsRef<Path>) -> Result<Vec<Session>, Error> {
+    let path = path.as_ref();
+    let log = match read_file(path) {
+        Ok(log) => log,
+        Err(Error::Io(err)) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err),
+    };
+
+    log.lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(number, line)| {
+            parse_session(line).map_err(|reason| {
+                Error::InvalidRolls(format!("{}:{number}: {reason}", path.display()))
+            })
+        })
+        .collect()
+}
+
+fn parse_session(line: &str) -> Result<Session, String> {
+    let fields: Vec<&str> = line.split('\t').collect();
+    let (time, counts, rolls) = match fields[..] {
+        [time, counts] => (time, counts, None),
+        [time, counts, rolls] => (time, counts, Some(rolls)),
+        _ => {
+            return Err(String::from(
+                "expected a time, counts per face and maybe rolls, separated by tabs",
+            ))
+        }
+    };
+
+    let time = time
+        .parse()
+        .map_err(|_| format!("\"{time}\" is not a time"))?;
+    let counts: Vec<u64> = counts
+        .split_whitespace()
+        .map(str::parse)
+        .collect::<Result<_, _>>()
+        .ok()
+        .filter(|counts: &Vec<u64>| counts.len() == FACES)
+        .ok_or_else(|| format!("expected {FACES} counts, one per face"))?;
+    let tally = Tally {
+        counts: counts.try_into().expect("there is a count per face"),
+    };
+
+    let rolls = rolls
+        .map(|rolls| parse_rolls(rolls).map_err(|err| err.to_string()))
+        .transpose()?;
+    if let Some(rolls) = &rolls {
+        let mut counted = Tally::new();
+        counted.add(rolls);
+        if counted != tally {
+            return Err(String::from("the counts don't match the rolls"));
+        }
+    }
+
+    Ok(Session { time, tally, rolls })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::PathBuf;
+
+    fn tally(counts: [u64; FACES]) -> Tally {
+        Tally { counts }
+    }
+
+    /// A path for a log of its own for every test.
+    fn log_path(test: &str) -> PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("spiceware-{test}-{}.log", std::process::id()));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn computes_chi_squared() {
+        assert_eq!(Tally::new().chi_squared(), 0.0);
+        assert_eq!(tally([10; FACES]).chi_squared(), 0.0);
+        assert!((tally([40, 0, 0, 0, 0, 0]).chi_squared() - 200.0).abs() < 1e-9);
+        // ((15 - 10)^2 + (5 - 10)^2) / 10
+        assert!((tally([15, 5, 10, 10, 10, 10]).chi_squared() - 5.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn only_uneven_counts_are_suspicious() {
+        assert!(!tally([5; FACES]).is_suspicious());
+        assert!(!tally([29, 0, 0, 0, 0, 0]).is_suspicious());
+        assert!(tally([30, 0, 0, 0, 0, 0]).is_suspicious());
+
+        // Chi-squared 13.8 and 16.0, around the critical value.
+        assert!(!tally([20, 5, 10, 10, 8, 7]).is_suspicious());
+        assert!(tally([21, 5, 9, 10, 8, 7]).is_suspicious());
+    }
+
+    #[test]
+    fn counts_rolls() {
+        let mut counted = Tally::new();
+        counted.add(&parse_rolls("16325 11").unwrap());
+        assert_eq!(counted.counts(), [3, 1, 1, 0, 1, 1]);
+
+        counted.merge(&tally([1; FACES]));
+        assert_eq!(counted.counts(), [4, 2, 2, 1, 2, 2]);
+        assert_eq!(counted.total(), 13);
+    }
+
+    #[test]
+    fn logs_round_trip() {
+        let path = log_path("round-trip");
+        assert_eq!(read_log(&path).unwrap(), []);
+
+        let sessions = [Session::new(&[1, 6, 3]), Session::with_rolls(&[2, 2, 5])];
+        for session in &sessions {
+            session.append_to_log(&path).unwrap();
+        }
+
+        let logged = read_log(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(logged, sessions);
+        assert_eq!(logged[0].rolls(), None);
+        assert_eq!(logged[1].rolls(), Some(&[2, 2, 5][..]));
+        assert_eq!(logged[1].tally().counts(), [0, 2, 0, 0, 1, 0]);
+    }
+
+    #[test]
+    fn rejects_broken_logs() {
+        let path = log_path("broken");
+        let lines = [
+            "1760774400\t1 2 3",
+            "1760774400 1 2 3 4 5 6",
+            "yesterday\t1 1 1 1 1 1",
+            "1760774400\t1 1 1 1 1 x",
+            "1760774400\t1 0 0 0 0 0\t7",
+            "1760774400\t1 0 0 0 0 0\t2",
+        ];
+        for line in lines {
+            fs::write(&path, format!("# a comment\n\n{line}\n")).unwrap();
+            let err = read_log(&path).unwrap_err().to_string();
+            assert!(err.contains(":3: "), "{line:?}: {err}");
+        }
+
+        fs::write(&path, "# a comment\n1760774400\t1 0 0 0 0 0\t1\n").unwrap();
+        let logged = read_log(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(logged[0].time(), 1760774400);
+    }
+}
@@ -0,0 +1,50 @@
+use num_bigint::BigUint;
+use std::fmt;
+
+/// How hard a passphrase is to guess, given full knowledge of how it was
+/// generated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entropy {
+    combinations: BigUint,
+}
+
+impl Entropy {
+    /// The entropy of picking uniformly among `combinations` possibilities.
+    pub fn from_combinations(combinations: BigUint) -> Self {
+        Self { combinations }
+    }
+
+    /// The entropy of picking `count` items independently from `choices`.
+    pub fn from_choices(choices: usize, count: u32) -> Self {
+        Self::from_combinations(BigUint::from(choices).pow(count))
+    }
+
+    /// The exact number of equally likely possibilities.
+    pub fn combinations(&self) -> &BigUint {
+        &self.combinations
+    }
+
+    /// The entropy in bits, i.e. the base 2 logarithm of the number of
+    /// possibilities.
+    pub fn bits(&self) -> f64 {
+        let bits = self.combinations.bits();
+        if bits <= 64 {
+            return (self.combinations.iter_u64_digits().next().unwrap_or(0) as f64).log2();
+        }
+
+        // Keep the 64 most significant bits; the rest doesn't change the
+        // result at f64 precision.
+        let shift = bits - 64;
+        let top = (&self.combinations >> shift)
+            .iter_u64_digits()
+            .next()
+            .unwrap_or(0);
+        (top as f64).log2() + shift as f64
+    }
+}
+
+impl fmt::Display for Entropy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:.1} bits", self.bits())
+    }
+}
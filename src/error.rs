@@ -0,0 +1,58 @@
+use std::{fmt, io};
+
+/// Everything that can go wrong in spiceware.
+#[derive(Debug)]
+pub enum Error {
+    /// Reading from or writing to a file failed.
+    Io(io::Error),
+    /// A wordlist was malformed.
+    InvalidWordlist(String),
+    /// Dice rolls were malformed or didn't fit the wordlist.
+    InvalidRolls(String),
+    /// Coin flips were malformed.
+    InvalidFlips(String),
+    /// Playing cards were malformed or dealt twice.
+    InvalidCards(String),
+    /// A word isn't part of the wordlist.
+    UnknownWord(String),
+    /// A delimiter can't be used the way it was asked to be.
+    InvalidDelimiter(String),
+    /// A passphrase doesn't have as much entropy as required.
+    TooWeak(String),
+    /// A feature that needs a random number generator was used without one.
+    RequiresRng(&'static str),
+    /// A source of randomness failed to produce random bytes.
+    EntropySource(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(err) => write!(f, "{err}"),
+            Error::InvalidWordlist(reason) => write!(f, "invalid wordlist: {reason}"),
+            Error::InvalidRolls(reason) => write!(f, "invalid dice rolls: {reason}"),
+            Error::InvalidFlips(reason) => write!(f, "invalid coin flips: {reason}"),
+            Error::InvalidCards(reason) => write!(f, "invalid cards: {reason}"),
+            Error::UnknownWord(word) => write!(f, "\"{word}\" is not in the wordlist"),
+            Error::InvalidDelimiter(reason) => write!(f, "invalid delimiter: {reason}"),
+            Error::TooWeak(reason) => write!(f, "passphrase too weak: {reason}"),
+            Error::RequiresRng(what) => write!(f, "{what} requires a random number generator"),
+            Error::EntropySource(reason) => write!(f, "entropy source failed: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Io(err)
+    }
+}
@@ -0,0 +1,81 @@
+use crate::source::uniform;
+use crate::Error;
+use rand::RngCore;
+
+/// The characters random digits are drawn from.
+pub const DIGITS: &str = "0123456789";
+
+/// The characters random symbols are drawn from, unless told otherwise.
+pub const DEFAULT_SYMBOLS: &str = "!#$%&*+=?@^_~";
+
+/// Where random digits and symbols go in a passphrase.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Placement {
+    /// After the last word, digits first.
+    #[default]
+    End,
+    /// Each one at the end of a randomly chosen word.
+    BetweenWords,
+    /// Each one at a random position, possibly in the middle of a word.
+    Anywhere,
+}
+
+/// Random digits and symbols mixed into a passphrase.
+#[derive(Debug, Clone)]
+pub(crate) struct Extras {
+    pub(crate) digits: u32,
+    pub(crate) symbols: u32,
+    pub(crate) symbol_set: Vec<char>,
+    pub(crate) placement: Placement,
+}
+
+impl Extras {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.digits == 0 && (self.symbols == 0 || self.symbol_set.is_empty())
+    }
+
+    /// Upper bound, in bytes, on the size of all extras together.
+    pub(crate) fn max_size(&self) -> usize {
+        let symbol_size = self.symbol_set.iter().map(|c| c.len_utf8()).max();
+        self.digits as usize + self.symbols as usize * symbol_size.unwrap_or(0)
+    }
+
+    /// Draw random digits and symbols, digits first.
+    pub(crate) fn draw<R: RngCore>(&self, rng: &mut R) -> Result<Vec<char>, Error> {
+        let digits: Vec<char> = DIGITS.chars().collect();
+        let mut extras = Vec::with_capacity((self.digits + self.symbols) as usize);
+        for _ in 0..self.digits {
+            extras.push(digits[uniform(rng, digits.len())?]);
+        }
+        if !self.symbol_set.is_empty() {
+            for _ in 0..self.symbols {
+                extras.push(self.symbol_set[uniform(rng, self.symbol_set.len())?]);
+            }
+        }
+
+        Ok(extras)
+    }
+}
+
+impl Default for Extras {
+    fn default() -> Self {
+        Self {
+            digits: 0,
+            symbols: 0,
+            symbol_set: DEFAULT_SYMBOLS.chars().collect(),
+            placement: Placement::default(),
+        }
+    }
+}
+
+/// The distinct characters of `s`, in order of first appearance.
+pub(crate) fn charset(s: &str) -> Vec<char> {
+    let mut chars: Vec<char> = Vec::with_capacity(s.len());
+    for c in s.chars() {
+        if !chars.contains(&c) {
+            chars.push(c);
+        }
+    }
+
+    chars
+}
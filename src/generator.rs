@@ -0,0 +1,643 @@
+use crate::decode::Analysis;
+use crate::delimiter::Delimiter;
+use crate::extras::{self, Extras, Placement};
+use crate::pool::Pool;
+use crate::source::uniform;
+use crate::{dice, Case, Check, Entropy, Error, Passphrase, Wordlist};
+use num_bigint::BigUint;
+use rand::rngs::ThreadRng;
+use rand::RngCore;
+use std::cell::OnceCell;
+use std::collections::HashSet;
+
+/// Generates passphrases by drawing random words from a [`Wordlist`].
+#[derive(Debug, Clone)]
+pub struct Generator<R = ThreadRng> {
+    wordlist: Wordlist,
+    num_words: u32,
+    delimiter: Delimiter,
+    case: Case,
+    extras: Extras,
+    exclude_ambiguous: bool,
+    analysis: OnceCell<Analysis>,
+    rng: R,
+}
+
+impl Generator {
+    /// Create a generator drawing from `wordlist`, producing four words
+    /// separated by spaces.
+    pub fn new(wordlist: Wordlist) -> Self {
+        Self {
+            wordlist,
+            num_words: 4,
+            delimiter: Delimiter::Fixed(String::from(" ")),
+            case: Case::default(),
+            extras: Extras::default(),
+            exclude_ambiguous: false,
+            analysis: OnceCell::new(),
+            rng: rand::thread_rng(),
+        }
+    }
+}
+
+impl Default for Generator {
+    fn default() -> Self {
+        Self::new(Wordlist::default())
+    }
+}
+
+impl<R: RngCore> Generator<R> {
+    /// Set the number of words a passphrase shall be made up of.
+    pub fn num_words(mut self, num_words: u32) -> Self {
+        self.num_words = num_words;
+        self
+    }
+
+    /// Set the string placed between words.
+    pub fn delimiter(mut self, delimiter: impl Into<String>) -> Self {
+        self.delimiter = Delimiter::Fixed(delimiter.into());
+        self.analysis = OnceCell::new();
+        self
+    }
+
+    /// Place a character drawn at random from `set` between every two words,
+    /// instead of a fixed delimiter. Repeated characters are only counted
+    /// once, and an empty set means no delimiter at all.
+    pub fn delimiter_set(mut self, set: &str) -> Self {
+        self.delimiter = Delimiter::random(set);
+        self.analysis = OnceCell::new();
+        self
+    }
+
+    /// Set how words are capitalized.
+    pub fn case(mut self, case: Case) -> Self {
+        self.case = case;
+        self.analysis = OnceCell::new();
+        self
+    }
+
+    /// Add this many random digits to every passphrase.
+    pub fn digits(mut self, digits: u32) -> Self {
+        self.extras.digits = digits;
+        self
+    }
+
+    /// Add this many random symbols to every passphrase.
+    pub fn symbols(mut self, symbols: u32) -> Self {
+        self.extras.symbols = symbols;
+        self
+    }
+
+    /// Set the characters symbols are drawn from, by default
+    /// [`extras::DEFAULT_SYMBOLS`]. Repeated characters are only counted once.
+    pub fn symbol_set(mut self, symbol_set: &str) -> Self {
+        self.extras.symbol_set = extras::charset(symbol_set);
+        self
+    }
+
+    /// Set where random digits and symbols go.
+    pub fn placement(mut self, placement: Placement) -> Self {
+        self.extras.placement = placement;
+        self
+    }
+
+    /// Never pick words that make passphrases ambiguous, see
+    /// [`Generator::ambiguous_words`].
+    pub fn exclude_ambiguous(mut self, exclude: bool) -> Self {
+        self.exclude_ambiguous = exclude;
+        self
+    }
+
+    /// Use `rng` to pick words instead of the thread-local RNG, see
+    /// [`source`](crate::source) for some alternatives.
+    pub fn rng<S: RngCore>(self, rng: S) -> Generator<S> {
+        Generator {
+            wordlist: self.wordlist,
+            num_words: self.num_words,
+            delimiter: self.delimiter,
+            case: self.case,
+            extras: self.extras,
+            exclude_ambiguous: self.exclude_ambiguous,
+            analysis: self.analysis,
+            rng,
+        }
+    }
+
+    /// The number of words a passphrase is made up of.
+    pub fn word_count(&self) -> u32 {
+        self.num_words
+    }
+
+    /// The list words are drawn from.
+    pub fn wordlist(&self) -> &Wordlist {
+        &self.wordlist
+    }
+
+    /// Upper bound, in bytes, on the size of a generated passphrase.
+    pub fn worst_case_passphrase_size(&self) -> usize {
+        let num_words = self.num_words as usize;
+        let delimiter_size = self.delimiter.max_size() * num_words.saturating_sub(1);
+        num_words * self.wordlist.max_size() + delimiter_size + self.extras.max_size()
+    }
+
+    /// Whether different choices of words always make for different
+    /// passphrases, see [`decode`](crate::decode).
+    pub fn is_uniquely_decodable(&self) -> bool {
+        self.analysis().uniquely_decodable
+    }
+
+    /// The words left out to make passphrases uniquely decodable, if they
+    /// aren't already.
+    ///
+    /// Passphrases containing these words may not be split back into words
+    /// unambiguously, or may be produced by more than one choice of words, so
+    /// they don't count towards the entropy of a passphrase.
+    pub fn ambiguous_words(&self) -> Vec<&str> {
+        self.analysis()
+            .ambiguous
+            .iter()
+            .filter_map(|&index| self.wordlist.get(index))
+            .collect()
+    }
+
+    fn analysis(&self) -> &Analysis {
+        self.analysis
+            .get_or_init(|| Analysis::new(&self.wordlist, &self.delimiter, self.case))
+    }
+
+    /// The words that count towards the entropy of a passphrase.
+    fn unambiguous_words(&self) -> impl Iterator<Item = &str> {
+        let analysis = self.analysis();
+        self.wordlist
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| !analysis.is_ambiguous(*index))
+            .map(|(_, word)| word)
+    }
+
+    /// The number of distinct passphrases this generator can produce.
+    ///
+    /// Only [unambiguous](Generator::ambiguous_words) words are counted,
+    /// which makes this a lower bound if there are ambiguous ones.
+    pub fn possible_combinations(&self) -> BigUint {
+        self.entropy().combinations().clone()
+    }
+
+    /// The entropy of the passphrases this generator produces.
+    pub fn entropy(&self) -> Entropy {
+        self.entropy_for(self.num_words)
+    }
+
+    /// The smallest number of words for which this generator's passphrases
+    /// have at least `bits` bits of entropy, or `None` if adding words doesn't
+    /// add entropy.
+    ///
+    /// `bits` must be finite.
+    pub fn num_words_for_bits(&self, bits: f64) -> Option<u32> {
+        let per_word = (self.unambiguous_words().count() as f64).log2();
+        if per_word <= 0.0 {
+            return (self.entropy_for(0).bits() >= bits).then_some(0);
+        }
+
+        let mut num_words = (bits / per_word).ceil().clamp(0.0, u32::MAX as f64) as u32;
+        while num_words > 0 && self.entropy_for(num_words - 1).bits() >= bits {
+            num_words -= 1;
+        }
+        while self.entropy_for(num_words).bits() < bits {
+            num_words += 1;
+        }
+
+        Some(num_words)
+    }
+
+    fn entropy_for(&self, num_words: u32) -> Entropy {
+        let unambiguous = self.unambiguous_words().count();
+        let mut combinations = BigUint::from(unambiguous).pow(num_words);
+        if self.case.is_random() && self.capitalization_is_distinct() && num_words > 0 {
+            combinations *= num_words;
+        }
+        combinations *= self.delimiter_combinations(num_words);
+        combinations *= self.extras_combinations();
+
+        Entropy::from_combinations(combinations)
+    }
+
+    /// The number of ways to pick random delimiters.
+    ///
+    /// They only count if none of them can be mistaken for a random digit or
+    /// symbol placed between the words.
+    fn delimiter_combinations(&self, num_words: u32) -> BigUint {
+        if !self.extras.is_empty() && self.extras.placement != Placement::End {
+            let mut extras: HashSet<char> = extras::DIGITS.chars().collect();
+            extras.extend(self.extras.symbol_set.iter());
+            if self.delimiter.chars().iter().any(|c| extras.contains(c)) {
+                return BigUint::from(1u32);
+            }
+        }
+
+        BigUint::from(self.delimiter.choices()).pow(num_words.saturating_sub(1))
+    }
+
+    /// The number of ways to pick random digits and symbols.
+    ///
+    /// When they are placed at random positions, only characters that can be
+    /// told apart from everything else in a passphrase are counted: reading
+    /// them back out in order recovers a uniformly random sequence, while the
+    /// positions themselves may collide and are never counted.
+    fn extras_combinations(&self) -> BigUint {
+        let digits: Vec<char> = extras::DIGITS.chars().collect();
+        let symbols = &self.extras.symbol_set;
+        let (count_digits, count_symbols) = match self.extras.placement {
+            Placement::End => (true, true),
+            Placement::BetweenWords | Placement::Anywhere => {
+                let mut others = self.word_chars();
+                others.extend(self.delimiter.chars());
+                let digits_distinct = digits
+                    .iter()
+                    .all(|c| !others.contains(c) && !symbols.contains(c));
+                let symbols_distinct = symbols
+                    .iter()
+                    .all(|c| !others.contains(c) && !digits.contains(c));
+                (digits_distinct, symbols_distinct)
+            }
+        };
+
+        let mut combinations = BigUint::from(1u32);
+        if count_digits {
+            combinations *= BigUint::from(digits.len()).pow(self.extras.digits);
+        }
+        if count_symbols && !symbols.is_empty() {
+            combinations *= BigUint::from(symbols.len()).pow(self.extras.symbols);
+        }
+
+        combinations
+    }
+
+    /// Every character that can appear in a word that counts towards the
+    /// entropy, in any case.
+    fn word_chars(&self) -> HashSet<char> {
+        self.unambiguous_words()
+            .flat_map(str::chars)
+            .flat_map(|c| c.to_lowercase().chain(c.to_uppercase()).chain([c]))
+            .collect()
+    }
+
+    /// Whether capitalizing a word always yields something that is neither the
+    /// word itself nor any other word of the list, so that every choice of
+    /// capitalized word makes for a distinct passphrase.
+    fn capitalization_is_distinct(&self) -> bool {
+        self.wordlist
+            .iter()
+            .all(|word| word.starts_with(char::is_lowercase) && !word.contains(char::is_uppercase))
+    }
+
+    /// Generate a new passphrase.
+    ///
+    /// Fails if the RNG fails to produce random bytes.
+    pub fn generate(&mut self) -> Result<Passphrase, Error> {
+        let indices = (0..self.num_words)
+            .map(|_| self.get_index())
+            .collect::<Result<Vec<_>, _>>()?;
+        let capitalized = if self.case.is_random() && self.num_words > 0 {
+            Some(uniform(&mut self.rng, self.num_words as usize)?)
+        } else {
+            None
+        };
+        let mut words = self.words(&indices, capitalized);
+        let delimiters = (1..indices.len())
+            .map(|_| self.delimiter.draw(&mut self.rng))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let extras = self.extras.draw(&mut self.rng)?;
+        if self.extras.placement == Placement::BetweenWords && !words.is_empty() {
+            for c in extras {
+                let i = uniform(&mut self.rng, words.len())?;
+                words[i].push(c);
+            }
+            return Ok(self.join(&words, &delimiters));
+        }
+
+        let mut passphrase = self.join(&words, &delimiters).into_string();
+        match self.extras.placement {
+            Placement::Anywhere => {
+                for c in extras {
+                    let position = uniform(&mut self.rng, passphrase.chars().count() + 1)?;
+                    let index = passphrase
+                        .char_indices()
+                        .nth(position)
+                        .map_or(passphrase.len(), |(index, _)| index);
+                    passphrase.insert(index, c);
+                }
+            }
+            Placement::End | Placement::BetweenWords => passphrase.extend(extras),
+        }
+
+        Ok(Passphrase::new(passphrase))
+    }
+
+    /// The number of dice rolls that pick a word.
+    ///
+    /// Fails if this generator's passphrases can't be made from dice rolls
+    /// alone.
+    pub fn dice_per_word(&self) -> Result<u32, Error> {
+        self.require_words_only()?;
+        self.wordlist.dice_per_word()
+    }
+
+    /// Fail if picking words isn't all there is to this generator's
+    /// passphrases, as is required to make them from dice, coins or cards.
+    fn require_words_only(&self) -> Result<(), Error> {
+        if self.case.is_random() {
+            return Err(Error::RequiresRng("capitalizing a random word"));
+        }
+        if self.delimiter.is_random() {
+            return Err(Error::RequiresRng("a delimiter set"));
+        }
+        if !self.extras.is_empty() {
+            return Err(Error::RequiresRng("adding digits or symbols"));
+        }
+
+        Ok(())
+    }
+
+    /// The index of the word picked by `rolls`, one roll per die.
+    ///
+    /// Fails if the word is excluded, in which case the dice have to be
+    /// rolled again.
+    pub fn index_for_rolls(&self, rolls: &[u8]) -> Result<usize, Error> {
+        let index = self.wordlist.index_for_rolls(rolls)?;
+        if self.is_excluded(index) {
+            return Err(Error::InvalidRolls(format!(
+                "{} picks an excluded word",
+                dice::format_rolls(rolls)
+            )));
+        }
+
+        Ok(index)
+    }
+
+    /// Build a passphrase from physical dice rolls instead of the RNG.
+    ///
+    /// `rolls` holds [`Generator::dice_per_word`] rolls for every word, in
+    /// order.
+    pub fn passphrase_from_rolls(&self, rolls: &[u8]) -> Result<Passphrase, Error> {
+        let num_dice = self.dice_per_word()?;
+
+        let expected = num_dice as usize * self.num_words as usize;
+        if rolls.len() != expected {
+            return Err(Error::InvalidRolls(format!(
+                "expected {expected} rolls, got {}",
+                rolls.len()
+            )));
+        }
+
+        let indices = rolls
+            .chunks(num_dice as usize)
+            .map(|rolls| self.index_for_rolls(rolls))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        self.passphrase_from_indices(&indices)
+    }
+
+    /// Draw the index of a word from coin flips or cards collected in `pool`,
+    /// or `None` if more are needed first.
+    ///
+    /// Excluded words are drawn again, like any other rejected draw.
+    pub fn index_from_pool(&self, pool: &mut Pool) -> Result<Option<usize>, Error> {
+        self.require_words_only()?;
+
+        while let Some(index) = pool.draw(self.wordlist.len()) {
+            if !self.is_excluded(index) {
+                return Ok(Some(index));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Build a passphrase from the indices of its words, picked without the
+    /// RNG, e.g. with [`Generator::index_from_pool`].
+    pub fn passphrase_from_indices(&self, indices: &[usize]) -> Result<Passphrase, Error> {
+        self.require_words_only()?;
+
+        if indices.len() != self.num_words as usize {
+            return Err(Error::InvalidWordlist(format!(
+                "expected {} words, got {}",
+                self.num_words,
+                indices.len()
+            )));
+        }
+        if let Some(index) = indices.iter().find(|&&index| index >= self.wordlist.len()) {
+            return Err(Error::InvalidWordlist(format!(
+                "there is no word at index {index}"
+            )));
+        }
+        if let Some(index) = indices.iter().find(|&&index| self.is_excluded(index)) {
+            return Err(Error::InvalidWordlist(format!(
+                "the word at index {index} is excluded"
+            )));
+        }
+
+        let Delimiter::Fixed(delimiter) = &self.delimiter else {
+            return Err(Error::RequiresRng("a delimiter set"));
+        };
+        let delimiters = vec![delimiter.clone(); indices.len().saturating_sub(1)];
+        Ok(self.join(&self.words(indices, None), &delimiters))
+    }
+
+    /// Split an existing passphrase into its words at this generator's
+    /// delimiter.
+    ///
+    /// Pieces between delimiters are joined back together where they make up
+    /// a word of the list, like `t-shirt` when the delimiter is `-`. Without a
+    /// delimiter, the passphrase is split into words of the list, failing if
+    /// that isn't possible.
+    pub fn split<'a>(&self, passphrase: &'a str) -> Result<Vec<&'a str>, Error> {
+        let words: HashSet<String> = self.wordlist.iter().map(str::to_lowercase).collect();
+        let is_word = |s: &str| words.contains(&s.to_lowercase());
+
+        let mut spans = Vec::new();
+        let mut start = 0;
+        match &self.delimiter {
+            Delimiter::Fixed(delimiter) if delimiter.is_empty() => {
+                return segment(passphrase, is_word).ok_or_else(|| {
+                    Error::InvalidDelimiter(String::from(
+                        "without a delimiter, the passphrase has to be made up of words of the list",
+                    ))
+                });
+            }
+            Delimiter::Fixed(delimiter) => {
+                for (i, _) in passphrase.match_indices(delimiter.as_str()) {
+                    spans.push((start, i));
+                    start = i + delimiter.len();
+                }
+            }
+            Delimiter::Random(set) => {
+                for (i, c) in passphrase.char_indices().filter(|(_, c)| set.contains(c)) {
+                    spans.push((start, i));
+                    start = i + c.len_utf8();
+                }
+            }
+        }
+        spans.push((start, passphrase.len()));
+
+        let mut split = Vec::with_capacity(spans.len());
+        let mut i = 0;
+        while i < spans.len() {
+            let (start, end) = spans[i];
+            let longest = (i + 1..spans.len())
+                .rev()
+                .find(|&j| is_word(&passphrase[start..spans[j].1]))
+                .unwrap_or(i);
+            let word = &passphrase[start..spans[longest].1.max(end)];
+            if !word.is_empty() {
+                split.push(word);
+            }
+            i = longest + 1;
+        }
+
+        Ok(split)
+    }
+
+    /// Check an existing passphrase against this generator's wordlist.
+    ///
+    /// Words are looked up as they are and in lowercase. The passphrase's
+    /// entropy is that of one generated from as many words as were found in
+    /// the list.
+    pub fn check(&self, passphrase: &str) -> Result<Check, Error> {
+        let (matched, unmatched): (Vec<&str>, Vec<&str>) =
+            self.split(passphrase)?.into_iter().partition(|word| {
+                self.wordlist.position(word).is_some()
+                    || self.wordlist.position(&word.to_lowercase()).is_some()
+            });
+
+        let entropy = self.entropy_for(matched.len() as u32);
+        Ok(Check::new(
+            matched.into_iter().map(String::from).collect(),
+            unmatched.into_iter().map(String::from).collect(),
+            entropy,
+        ))
+    }
+
+    fn get_index(&mut self) -> Result<usize, Error> {
+        loop {
+            let index = uniform(&mut self.rng, self.wordlist.len())?;
+            if !self.is_excluded(index) {
+                return Ok(index);
+            }
+        }
+    }
+
+    fn is_excluded(&self, index: usize) -> bool {
+        self.exclude_ambiguous && self.analysis().is_ambiguous(index)
+    }
+
+    fn words(&self, indices: &[usize], capitalized: Option<usize>) -> Vec<String> {
+        indices
+            .iter()
+            .enumerate()
+            .map(|(i, &index)| {
+                let word = self.wordlist.get(index).expect("index is within bounds");
+                self.case.apply(word, i, capitalized)
+            })
+            .collect()
+    }
+
+    /// Join `words`, putting `delimiters[i]` between `words[i]` and
+    /// `words[i + 1]`.
+    fn join(&self, words: &[String], delimiters: &[String]) -> Passphrase {
+        let mut passphrase = String::with_capacity(self.worst_case_passphrase_size());
+        for (i, word) in words.iter().enumerate() {
+            if i > 0 {
+                passphrase.push_str(&delimiters[i - 1]);
+            }
+            passphrase.push_str(word);
+        }
+
+        Passphrase::new(passphrase)
+    }
+}
+
+/// Split `s` into words for which `is_word` holds, if possible.
+fn segment(s: &str, is_word: impl Fn(&str) -> bool) -> Option<Vec<&str>> {
+    // For every position in `s`, where the word ending there starts, if `s`
+    // can be split up to there.
+    let mut starts: Vec<Option<usize>> = vec![None; s.len() + 1];
+    starts[0] = Some(0);
+    let boundaries: Vec<usize> = s.char_indices().map(|(i, _)| i).chain([s.len()]).collect();
+    for (k, &end) in boundaries.iter().enumerate().skip(1) {
+        starts[end] = boundaries[..k]
+            .iter()
+            .copied()
+            .find(|&start| starts[start].is_some() && is_word(&s[start..end]));
+    }
+
+    let mut words = Vec::new();
+    let mut end = s.len();
+    while end > 0 {
+        let start = starts[end]?;
+        words.push(&s[start..end]);
+        end = start;
+    }
+    words.reverse();
+
+    Some(words)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn generator(words: &str, delimiter: &str) -> Generator {
+        Generator::new(Wordlist::parse(words).unwrap()).delimiter(delimiter)
+    }
+
+    #[test]
+    fn finds_words_for_bits_without_word_entropy() {
+        // With no delimiter, "aa" is ambiguous, which leaves a single word.
+        let generator = generator("a\naa\n", "");
+        assert_eq!(generator.num_words_for_bits(1.0), None);
+        assert_eq!(generator.digits(1).num_words_for_bits(1.0), Some(0));
+    }
+
+    #[test]
+    fn empty_delimiter_sets_mean_no_delimiter() {
+        let mut generator = generator("cat\ndog\nhorse\n", "").delimiter_set("");
+        assert!(generator.entropy().bits().is_finite());
+        assert_eq!(generator.possible_combinations(), BigUint::from(81u32));
+        let passphrase = generator.generate().unwrap();
+        assert!(!passphrase.as_str().contains(' '));
+    }
+
+    #[test]
+    fn joins_pieces_of_words_containing_the_delimiter() {
+        let generator = generator("drop\ndown\ndrop-down\n", "-");
+        assert_eq!(generator.ambiguous_words(), ["drop-down"]);
+        assert_eq!(
+            generator.split("drop-down-down").unwrap(),
+            ["drop-down", "down"]
+        );
+        assert_eq!(generator.split("down-drop").unwrap(), ["down", "drop"]);
+        assert_eq!(generator.split("drop-nope").unwrap(), ["drop", "nope"]);
+    }
+
+    #[test]
+    fn segments_passphrases_without_a_delimiter() {
+        let generator = generator("cat\ndog\nhorse\n", "");
+        assert!(generator.is_uniquely_decodable());
+        assert_eq!(
+            generator.split("catdoghorse").unwrap(),
+            ["cat", "dog", "horse"]
+        );
+        assert_eq!(generator.split("CatDog").unwrap(), ["Cat", "Dog"]);
+        assert!(generator.split("catdo").is_err());
+    }
+
+    #[test]
+    fn segments_words_that_are_prefixes_of_others() {
+        let generator = generator("a\nab\nbc\n", "");
+        assert!(generator.is_uniquely_decodable());
+        assert_eq!(generator.split("abca").unwrap(), ["a", "bc", "a"]);
+        assert_eq!(generator.split("abbca").unwrap(), ["ab", "bc", "a"]);
+    }
+}
@@ -0,0 +1,297 @@
+//! Continuous health tests for sources of randomness, after NIST SP 800-90B,
+//! section 4.4.
+//!
+//! Every byte a source produces is fed through two tests, which catch a
+//! source that got stuck or became badly biased:
+//!
+//! - the repetition count test fails when the same byte comes up
+//!   [`REPETITION_CUTOFF`] times in a row;
+//! - the adaptive proportion test fails when the first byte of a window of
+//!   [`WINDOW_SIZE`] bytes comes up [`PROPORTION_CUTOFF`] times within it.
+//!
+//! The cutoffs assume that every byte carries a full eight bits of entropy,
+//! as the bytes spiceware picks words with should, and make a false alarm as
+//! unlikely as 2^-40 per byte.
+
+use rand::RngCore;
+use std::fmt;
+
+/// How many times in a row the same byte may come up before the repetition
+/// count test fails.
+pub const REPETITION_CUTOFF: usize = 6;
+
+/// The number of bytes in a window of the adaptive proportion test.
+pub const WINDOW_SIZE: usize = 512;
+
+/// How many times the first byte of a window may come up within it before the
+/// adaptive proportion test fails.
+pub const PROPORTION_CUTOFF: usize = 19;
+
+/// The number of bytes tested before a source is first used.
+pub const STARTUP_SAMPLES: usize = 1024;
+
+/// A source of randomness whose bytes pass the health tests before they are
+/// used.
+///
+/// Before any bytes are handed out, a startup self-test checks that the tests
+/// catch a stuck source, then runs [`STARTUP_SAMPLES`] bytes from the source
+/// through them. Those bytes aren't thrown away but handed out first, so
+/// wrapping a source doesn't change the stream of bytes it produces. Once a
+/// test fails, every further request for bytes fails too.
+#[derive(Debug)]
+pub struct HealthChecked<R> {
+    rng: R,
+    tests: Tests,
+    /// Startup bytes not handed out yet.
+    pending: Vec<u8>,
+    started: bool,
+    failure: Option<Failure>,
+}
+
+impl<R: RngCore> HealthChecked<R> {
+    /// Test the bytes `rng` produces.
+    pub fn new(rng: R) -> Self {
+        Self {
+            rng,
+            tests: Tests::default(),
+            pending: Vec::new(),
+            started: false,
+            failure: None,
+        }
+    }
+
+    /// Run the startup self-test now, instead of when bytes are first needed.
+    pub fn start(&mut self) -> Result<(), rand::Error> {
+        if let Some(failure) = self.failure {
+            return Err(rand::Error::new(failure));
+        }
+        if self.started {
+            return Ok(());
+        }
+
+        if let Err(failure) = self_test() {
+            return Err(self.fail(failure));
+        }
+
+        let mut samples = vec![0; STARTUP_SAMPLES];
+        self.rng.try_fill_bytes(&mut samples).map_err(|err| {
+            rand::Error::new(format!(
+                "the startup health test needs {STARTUP_SAMPLES} bytes: {err}"
+            ))
+        })?;
+        if let Err(failure) = self.tests.feed(&samples) {
+            return Err(self.fail(failure));
+        }
+
+        // Handed out from the back.
+        samples.reverse();
+        self.pending = samples;
+        self.started = true;
+        Ok(())
+    }
+
+    fn fail(&mut self, failure: Failure) -> rand::Error {
+        self.failure = Some(failure);
+        self.pending.clear();
+        rand::Error::new(failure)
+    }
+}
+
+impl<R: RngCore> RngCore for HealthChecked<R> {
+    fn next_u32(&mut self) -> u32 {
+        rand_core::impls::next_u32_via_fill(self)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        rand_core::impls::next_u64_via_fill(self)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        if let Err(err) = self.try_fill_bytes(dest) {
+            panic!("{err}");
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.start()?;
+
+        let from_pending = dest.len().min(self.pending.len());
+        let (startup, rest) = dest.split_at_mut(from_pending);
+        for byte in startup {
+            *byte = self.pending.pop().expect("enough bytes are pending");
+        }
+
+        self.rng.try_fill_bytes(rest)?;
+        if let Err(failure) = self.tests.feed(rest) {
+            return Err(self.fail(failure));
+        }
+
+        Ok(())
+    }
+}
+
+/// Why a health test failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Failure {
+    SelfTest,
+    RepetitionCount(u8),
+    AdaptiveProportion(u8),
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Failure::SelfTest => write!(f, "health tests failed their self-test"),
+            Failure::RepetitionCount(byte) => write!(
+                f,
+                "repetition count test failed, byte {byte:#04x} came up {REPETITION_CUTOFF} times in a row"
+            ),
+            Failure::AdaptiveProportion(byte) => write!(
+                f,
+                "adaptive proportion test failed, byte {byte:#04x} came up {PROPORTION_CUTOFF} times in {WINDOW_SIZE} bytes"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Failure {}
+
+/// The state of both tests.
+#[derive(Debug, Clone, Default)]
+struct Tests {
+    /// The last byte and how many times in a row it came up.
+    repetition: Option<(u8, usize)>,
+    /// The first byte of the current window, how many times it came up, and
+    /// how many bytes of the window were seen.
+    proportion: Option<(u8, usize, usize)>,
+}
+
+impl Tests {
+    fn feed(&mut self, bytes: &[u8]) -> Result<(), Failure> {
+        bytes.iter().try_for_each(|&byte| self.feed_byte(byte))
+    }
+
+    fn feed_byte(&mut self, byte: u8) -> Result<(), Failure> {
+        let repetitions = match self.repetition {
+            Some((last, count)) if last == byte => count + 1,
+            _ => 1,
+        };
+        self.repetition = Some((byte, repetitions));
+        if repetitions >= REPETITION_CUTOFF {
+            return Err(Failure::RepetitionCount(byte));
+        }
+
+        self.proportion = match self.proportion {
+            Some((first, count, seen)) if seen < WINDOW_SIZE => {
+                let count = count + usize::from(byte == first);
+                if count >= PROPORTION_CUTOFF {
+                    return Err(Failure::AdaptiveProportion(first));
+                }
+                Some((first, count, seen + 1))
+            }
+            _ => Some((byte, 1, 1)),
+        };
+
+        Ok(())
+    }
+}
+
+/// Check that the tests catch a source stuck on a single byte, and one that
+/// keeps coming back to it.
+fn self_test() -> Result<(), Failure> {
+    let stuck = [0x55; REPETITION_CUTOFF];
+    if Tests::default().feed(&stuck) != Err(Failure::RepetitionCount(0x55)) {
+        return Err(Failure::SelfTest);
+    }
+
+    let biased: Vec<u8> = (0..WINDOW_SIZE)
+        .map(|i| if i % 2 == 0 { 0x55 } else { i as u8 })
+        .collect();
+    if Tests::default().feed(&biased) != Err(Failure::AdaptiveProportion(0x55)) {
+        return Err(Failure::SelfTest);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source;
+    use rand::rngs::mock::StepRng;
+
+    #[test]
+    fn repetition_count_test_fails_at_the_cutoff() {
+        let mut tests = Tests::default();
+        assert_eq!(tests.feed(&[0x42; REPETITION_CUTOFF - 1]), Ok(()));
+        assert_eq!(tests.feed(&[0x42]), Err(Failure::RepetitionCount(0x42)));
+
+        let mut tests = Tests::default();
+        assert_eq!(tests.feed(&[0x42; REPETITION_CUTOFF - 1]), Ok(()));
+        assert_eq!(tests.feed(&[0x43; REPETITION_CUTOFF - 1]), Ok(()));
+    }
+
+    /// A window starting with `copies` copies of 0xaa spread out among bytes
+    /// that differ from it and each other.
+    fn window(copies: usize) -> Vec<u8> {
+        (0..WINDOW_SIZE)
+            .map(|i| {
+                if i % 20 == 0 && i / 20 < copies {
+                    0xaa
+                } else {
+                    (i % 100) as u8
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn adaptive_proportion_test_fails_at_the_cutoff() {
+        assert_eq!(
+            Tests::default().feed(&window(PROPORTION_CUTOFF)),
+            Err(Failure::AdaptiveProportion(0xaa))
+        );
+        assert_eq!(
+            Tests::default().feed(&window(PROPORTION_CUTOFF - 1)),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn adaptive_proportion_test_starts_a_new_window() {
+        let mut tests = Tests::default();
+        assert_eq!(tests.feed(&window(PROPORTION_CUTOFF - 1)), Ok(()));
+        assert_eq!(tests.feed(&window(PROPORTION_CUTOFF - 1)), Ok(()));
+    }
+
+    #[test]
+    fn self_test_passes() {
+        assert_eq!(self_test(), Ok(()));
+    }
+
+    #[test]
+    fn leaves_the_output_of_a_source_unchanged() {
+        let sizes = [8, 1, 7, 1000, 4096];
+        let mut expected = vec![0; sizes.iter().sum()];
+        source::seeded(b"spiceware").fill_bytes(&mut expected);
+
+        let mut checked = HealthChecked::new(source::seeded(b"spiceware"));
+        let mut actual = Vec::new();
+        for size in sizes {
+            let mut bytes = vec![0; size];
+            checked.try_fill_bytes(&mut bytes).unwrap();
+            actual.extend(bytes);
+        }
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn stuck_sources_fail_for_good() {
+        let mut checked = HealthChecked::new(StepRng::new(0, 0));
+        let mut bytes = [0; 8];
+        let err = checked.try_fill_bytes(&mut bytes).unwrap_err();
+        assert!(err.to_string().contains("repetition count test failed"));
+        assert!(checked.try_fill_bytes(&mut bytes).is_err());
+        assert!(checked.start().is_err());
+    }
+}
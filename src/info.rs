@@ -0,0 +1,57 @@
+/// What's known about a [`Wordlist`](crate::Wordlist): where it's from, and
+/// what it's made of.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WordlistInfo {
+    pub(crate) name: String,
+    pub(crate) source: Option<String>,
+    pub(crate) license: Option<String>,
+    pub(crate) language: Option<String>,
+    pub(crate) len: usize,
+    pub(crate) dice_per_word: Option<u32>,
+    pub(crate) max_size: usize,
+}
+
+impl WordlistInfo {
+    /// The name of the list, e.g. `eff-large`, or the name of the file it was
+    /// loaded from.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Where the list comes from, e.g. a URL or the path of the file it was
+    /// loaded from.
+    pub fn source(&self) -> Option<&str> {
+        self.source.as_deref()
+    }
+
+    /// The license the list is distributed under, if known.
+    pub fn license(&self) -> Option<&str> {
+        self.license.as_deref()
+    }
+
+    /// The language of the words, as an ISO 639-1 code like `en`, if known.
+    pub fn language(&self) -> Option<&str> {
+        self.language.as_deref()
+    }
+
+    /// The number of words in the list.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the list contains no words at all.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The number of dice rolls that pick a word, if the size of the list is a
+    /// power of six.
+    pub fn dice_per_word(&self) -> Option<u32> {
+        self.dice_per_word
+    }
+
+    /// Size, in bytes, of the largest word in the list.
+    pub fn max_size(&self) -> usize {
+        self.max_size
+    }
+}
@@ -0,0 +1,40 @@
+//! Generate diceware-like passphrases.
+//!
+//! ```no_run
+//! use spiceware::{Generator, Wordlist};
+//!
+//! let mut generator = Generator::new(Wordlist::large()).num_words(6).delimiter("-");
+//! println!("{}", generator.generate()?);
+//! # Ok::<(), spiceware::Error>(())
+//! ```
+
+mod case;
+mod check;
+pub mod corpus;
+pub mod decode;
+mod delimiter;
+pub mod dice;
+mod entropy;
+mod error;
+pub mod extras;
+mod generator;
+pub mod health;
+mod info;
+pub mod lint;
+mod packed;
+mod passphrase;
+pub mod pool;
+pub mod short_words;
+pub mod source;
+mod wordlist;
+pub mod words;
+
+pub use case::Case;
+pub use check::Check;
+pub use entropy::Entropy;
+pub use error::Error;
+pub use generator::Generator;
+pub use info::WordlistInfo;
+pub use packed::PackedWords;
+pub use passphrase::Passphrase;
+pub use wordlist::Wordlist;
@@ -0,0 +1,438 @@
+//! Quality checks for wordlists.
+
+use crate::wordlist::{parse_words, prefix_len, read_file};
+use crate::{dice, Error, Wordlist};
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::Path;
+
+/// How bad a finding is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// Worth knowing, but not a problem in itself.
+    Note,
+    /// Probably a problem, depending on how the list is used.
+    Warning,
+    /// Makes the list unfit for generating passphrases.
+    Error,
+}
+
+/// Something a [`Report`] found out about a wordlist.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    severity: Severity,
+    message: String,
+}
+
+impl Finding {
+    /// How bad this finding is.
+    pub fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    /// What was found.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Note => "note",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        write!(f, "{severity}: {}", self.message)
+    }
+}
+
+/// The outcome of linting a wordlist.
+#[derive(Debug, Clone)]
+pub struct Report {
+    len: usize,
+    max_size: usize,
+    min_edit_distance: Option<(usize, String, String)>,
+    findings: Vec<Finding>,
+}
+
+impl Report {
+    /// The number of words in the list.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the list contains no words at all.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Size, in bytes, of the largest word in the list.
+    pub fn max_size(&self) -> usize {
+        self.max_size
+    }
+
+    /// The smallest number of single character edits that turn one word of
+    /// the list into another, along with two such words.
+    pub fn min_edit_distance(&self) -> Option<(usize, &str, &str)> {
+        self.min_edit_distance
+            .as_ref()
+            .map(|(distance, a, b)| (*distance, a.as_str(), b.as_str()))
+    }
+
+    /// Everything found, most severe first.
+    pub fn findings(&self) -> &[Finding] {
+        &self.findings
+    }
+
+    /// Whether the list is unfit for generating passphrases.
+    pub fn has_errors(&self) -> bool {
+        self.findings
+            .iter()
+            .any(|finding| finding.severity == Severity::Error)
+    }
+}
+
+/// Lint a loaded or built-in wordlist.
+pub fn lint_wordlist(wordlist: &Wordlist) -> Report {
+    let words: Vec<&str> = wordlist.iter().collect();
+    lint(&words, Some(wordlist.max_size()))
+}
+
+/// Lint the wordlist file at `path`, in any format [`Wordlist::parse`]
+/// accepts.
+pub fn lint_file(path: impl AsRef<Path>) -> Result<Report, Error> {
+    let text = read_file(path.as_ref())?;
+    Ok(lint(&parse_words(&text)?, None))
+}
+
+/// Lint `words`, `declared_max_size` being the size of the largest word as
+/// stated by the list, if it states one.
+pub fn lint(words: &[&str], declared_max_size: Option<usize>) -> Report {
+    let mut findings = Vec::new();
+    let mut find = |severity, message| findings.push(Finding { severity, message });
+
+    if words.len() < 2 {
+        find(Severity::Error, String::from("fewer than two words"));
+    }
+
+    let max_size = words.iter().map(|word| word.len()).max().unwrap_or(0);
+    if let Some(declared) = declared_max_size.filter(|declared| *declared != max_size) {
+        find(
+            Severity::Error,
+            format!("the largest word is {max_size} bytes long, but {declared} are declared"),
+        );
+    }
+
+    let mut seen = HashSet::new();
+    let duplicates: Vec<&str> = words
+        .iter()
+        .copied()
+        .filter(|word| !seen.insert(*word))
+        .collect();
+    let mut unique: Vec<&str> = seen.into_iter().collect();
+    unique.sort_unstable();
+    if !duplicates.is_empty() {
+        find(
+            Severity::Error,
+            format!("duplicate words: {}", examples(&duplicates)),
+        );
+    }
+
+    let mut lowercase: HashMap<String, &str> = HashMap::new();
+    let collisions: Vec<String> = unique
+        .iter()
+        .filter_map(|word| {
+            let other = lowercase.insert(word.to_lowercase(), word)?;
+            Some(format!("{other}/{word}"))
+        })
+        .collect();
+    if !collisions.is_empty() {
+        find(
+            Severity::Error,
+            format!("words that differ only in case: {}", examples(&collisions)),
+        );
+    }
+
+    let with_whitespace: Vec<&str> = words
+        .iter()
+        .copied()
+        .filter(|word| word.is_empty() || word.contains(char::is_whitespace))
+        .collect();
+    if !with_whitespace.is_empty() {
+        find(
+            Severity::Error,
+            format!(
+                "empty words or words containing whitespace: {}",
+                examples(&with_whitespace)
+            ),
+        );
+    }
+
+    let with_punctuation: Vec<&str> = words
+        .iter()
+        .copied()
+        .filter(|word| word.contains(|c: char| !c.is_alphanumeric() && !c.is_whitespace()))
+        .collect();
+    if !with_punctuation.is_empty() {
+        find(
+            Severity::Warning,
+            format!(
+                "words containing punctuation: {}",
+                examples(&with_punctuation)
+            ),
+        );
+    }
+
+    let non_ascii: Vec<&str> = words
+        .iter()
+        .copied()
+        .filter(|word| !word.is_ascii())
+        .collect();
+    if !non_ascii.is_empty() {
+        find(
+            Severity::Warning,
+            format!("non-ASCII words: {}", examples(&non_ascii)),
+        );
+    }
+
+    match dice::dice_for(words.len()) {
+        Some(num_dice) => find(
+            Severity::Note,
+            format!("{} words, {num_dice} dice per word", words.len()),
+        ),
+        None => find(
+            Severity::Warning,
+            format!(
+                "{} words is not a power of six, so the list can't be used with dice",
+                words.len()
+            ),
+        ),
+    }
+
+    let prefixes = prefix_pairs(&unique);
+    if !prefixes.is_empty() {
+        find(
+            Severity::Note,
+            format!(
+                "words that are prefixes of other words: {}",
+                examples(&prefixes)
+            ),
+        );
+    }
+
+    if unique.len() > 1 {
+        find(
+            Severity::Note,
+            format!(
+                "words are told apart by their first {} characters",
+                prefix_len(&unique)
+            ),
+        );
+    }
+
+    let min_edit_distance = min_edit_distance(&unique);
+    if let Some((distance, a, b)) = &min_edit_distance {
+        find(
+            Severity::Note,
+            format!("minimum edit distance between words is {distance}, e.g. {a}/{b}"),
+        );
+    }
+
+    findings.sort_by_key(|finding| Reverse(finding.severity));
+    Report {
+        len: words.len(),
+        max_size,
+        min_edit_distance,
+        findings,
+    }
+}
+
+/// Up to five of `items` separated by commas, mentioning how many were left
+/// out.
+fn examples<S: AsRef<str>>(items: &[S]) -> String {
+    const SHOWN: usize = 5;
+    let shown: Vec<&str> = items.iter().take(SHOWN).map(AsRef::as_ref).collect();
+    match items.len() {
+        n if n > SHOWN => format!("{}, ... ({n} in total)", shown.join(", ")),
+        _ => shown.join(", "),
+    }
+}
+
+/// Words that are prefixes of other words, as `prefix/word`, given distinct
+/// `words` in sorted order.
+fn prefix_pairs(words: &[&str]) -> Vec<String> {
+    // A word that is a prefix of others is directly followed by one of them.
+    words
+        .windows(2)
+        .filter(|pair| pair[1].starts_with(pair[0]))
+        .map(|pair| format!("{}/{}", pair[0], pair[1]))
+        .collect()
+}
+
+/// The smallest Levenshtein distance between any two distinct words.
+fn min_edit_distance(words: &[&str]) -> Option<(usize, String, String)> {
+    let mut words: Vec<Vec<char>> = words.iter().map(|word| word.chars().collect()).collect();
+    words.sort_unstable_by(|a, b| a.len().cmp(&b.len()).then_with(|| a.cmp(b)));
+
+    let mut best: Option<(usize, usize, usize)> = None;
+    for i in 0..words.len() {
+        for j in i + 1..words.len() {
+            let bound = best.map_or(usize::MAX, |(distance, _, _)| distance);
+            // Sorted by length, so all further words are at least this far.
+            if words[j].len() - words[i].len() >= bound {
+                break;
+            }
+
+            let distance = bounded_levenshtein(&words[i], &words[j], bound);
+            if distance < bound {
+                best = Some((distance, i, j));
+                if distance == 1 {
+                    break;
+                }
+            }
+        }
+
+        if best.is_some_and(|(distance, _, _)| distance == 1) {
+            break;
+        }
+    }
+
+    best.map(|(distance, i, j)| {
+        let word = |k: usize| words[k].iter().collect::<String>();
+        (distance, word(i), word(j))
+    })
+}
+
+/// The Levenshtein distance between `a` and `b`, or `bound` if it is at least
+/// that.
+pub(crate) fn bounded_levenshtein(a: &[char], b: &[char], bound: usize) -> usize {
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for (i, ca) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+
+        if current.iter().all(|&distance| distance >= bound) {
+            return bound;
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    previous[b.len()].min(bound)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn messages(report: &Report, severity: Severity) -> Vec<&str> {
+        report
+            .findings()
+            .iter()
+            .filter(|finding| finding.severity() == severity)
+            .map(Finding::message)
+            .collect()
+    }
+
+    fn chars(word: &str) -> Vec<char> {
+        word.chars().collect()
+    }
+
+    #[test]
+    fn finds_duplicates_and_case_collisions() {
+        let report = lint(&["apple", "Apple", "pear", "apple"], None);
+        assert!(report.has_errors());
+        assert_eq!(
+            messages(&report, Severity::Error),
+            [
+                "duplicate words: apple",
+                "words that differ only in case: Apple/apple"
+            ]
+        );
+    }
+
+    #[test]
+    fn checks_the_declared_max_size() {
+        let report = lint(&["fig", "melon"], Some(5));
+        assert!(!report.has_errors());
+        assert_eq!(report.max_size(), 5);
+
+        let report = lint(&["fig", "melon"], Some(9));
+        assert_eq!(
+            messages(&report, Severity::Error),
+            ["the largest word is 5 bytes long, but 9 are declared"]
+        );
+    }
+
+    #[test]
+    fn flags_whitespace_punctuation_and_non_ascii() {
+        let report = lint(&["t-shirt", "café", "two words", ""], None);
+        assert_eq!(
+            messages(&report, Severity::Error),
+            ["empty words or words containing whitespace: two words, "]
+        );
+        let warnings = messages(&report, Severity::Warning);
+        assert!(warnings.contains(&"words containing punctuation: t-shirt"));
+        assert!(warnings.contains(&"non-ASCII words: café"));
+        assert!(warnings
+            .contains(&"4 words is not a power of six, so the list can't be used with dice"));
+    }
+
+    #[test]
+    fn lists_words_that_are_prefixes_of_others() {
+        // Each prefix is named once, with the word right after it.
+        assert_eq!(
+            prefix_pairs(&["car", "card", "care", "cart", "dog", "dogma"]),
+            ["car/card", "dog/dogma"]
+        );
+        assert!(prefix_pairs(&["card", "care"]).is_empty());
+
+        let report = lint(&["cart", "car", "dog"], None);
+        assert!(messages(&report, Severity::Note)
+            .contains(&"words that are prefixes of other words: car/cart"));
+    }
+
+    #[test]
+    fn computes_bounded_levenshtein_distances() {
+        let distance = |a, b, bound| bounded_levenshtein(&chars(a), &chars(b), bound);
+        assert_eq!(distance("kitten", "sitting", usize::MAX), 3);
+        assert_eq!(distance("flaw", "lawn", usize::MAX), 2);
+        assert_eq!(distance("", "abc", usize::MAX), 3);
+        assert_eq!(distance("same", "same", usize::MAX), 0);
+
+        // Gives up once every path is at least as long as the bound.
+        assert_eq!(distance("kitten", "sitting", 3), 3);
+        assert_eq!(distance("kitten", "sitting", 2), 2);
+        assert_eq!(distance("abcdef", "uvwxyz", 1), 1);
+    }
+
+    #[test]
+    fn finds_the_minimum_edit_distance() {
+        let report = lint(&["abacus", "zebra", "cobra", "kitten"], None);
+        assert_eq!(report.min_edit_distance(), Some((2, "cobra", "zebra")));
+
+        // Words far apart in length are never compared in full.
+        let report = lint(&["a", "bcdefghij", "bcdefghik"], None);
+        assert_eq!(
+            report.min_edit_distance(),
+            Some((1, "bcdefghij", "bcdefghik"))
+        );
+
+        assert_eq!(lint(&["solo"], None).min_edit_distance(), None);
+    }
+
+    #[test]
+    fn sorts_findings_by_severity() {
+        let report = lint(&["ok", "t-shirt", "ok"], None);
+        let severities: Vec<Severity> = report.findings().iter().map(Finding::severity).collect();
+        let mut sorted = severities.clone();
+        sorted.sort_by_key(|severity| Reverse(*severity));
+        assert_eq!(severities, sorted);
+        assert_eq!(severities[0], Severity::Error);
+    }
+}
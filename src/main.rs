@@ -1,25 +1,52 @@
-mod short_words;
-mod words;
+use clap::builder::PossibleValuesParser;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use rand::RngCore;
+use spiceware::corpus::{Corpus, ListBuilder};
+use spiceware::dice::{Session, Tally, CRITICAL_VALUE};
+use spiceware::extras::{self, Placement};
+use spiceware::health::HealthChecked;
+use spiceware::pool::Pool;
+use spiceware::{dice, Case, Error, Generator, Passphrase, Wordlist, WordlistInfo};
+use spiceware::{lint, source};
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
 
-use clap::Parser;
-use rand::Rng;
-use short_words::SHORT_WORDS;
-use words::WORDS;
+/// Whichever source of randomness was asked for.
+type Rng = Box<dyn RngCore>;
+
+/// Entropy targets beyond this are surely typos.
+const MAX_BITS: f64 = 4096.0;
 
 /// Generate diceware-like passphrases
 #[derive(Parser)]
 #[command(version)]
 struct Spiceware {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// The number of words a passphrase shall be made up of
     #[clap(
         short = 'w',
         long = "words",
         value_name = "n",
         required = false,
-        default_value = "4"
+        default_value = "4",
+        value_parser = clap::value_parser!(u32).range(1..)
     )]
     num_words: u32,
 
+    /// Use as few words as needed, but at least one, for at least this many
+    /// bits of entropy
+    #[clap(
+        short = 'b',
+        long = "bits",
+        value_name = "n",
+        conflicts_with = "num_words",
+        value_parser = parse_bits
+    )]
+    bits: Option<f64>,
+
     /// The number of passphrases to generate
     #[clap(
         short = 'n',
@@ -30,93 +57,810 @@ struct Spiceware {
     )]
     num_passwords: u32,
 
-    #[clap(short = 'd', long = "delimiter", default_value = " ")]
+    #[clap(short = 'd', long = "delimiter", default_value = " ", global = true)]
     delimiter: String,
 
+    /// Put a character drawn at random from this set between every two words
+    #[clap(
+        long = "delimiter-set",
+        value_name = "chars",
+        conflicts_with = "delimiter",
+        value_parser = parse_charset,
+        allow_hyphen_values = true,
+        global = true
+    )]
+    delimiter_set: Option<String>,
+
+    /// How to capitalize words
+    #[clap(short = 'c', long = "case", value_enum, default_value = "lower")]
+    case: CaseArg,
+
+    /// The number of random digits to add
+    #[clap(long = "digits", value_name = "n", default_value = "0")]
+    digits: u32,
+
+    /// The number of random symbols to add
+    #[clap(long = "symbols", value_name = "n", default_value = "0")]
+    symbols: u32,
+
+    /// The characters random symbols are drawn from
+    #[clap(
+        long = "symbol-set",
+        value_name = "chars",
+        default_value = extras::DEFAULT_SYMBOLS,
+        value_parser = parse_charset,
+        allow_hyphen_values = true
+    )]
+    symbol_set: String,
+
+    /// Where to put random digits and symbols
+    #[clap(long = "placement", value_enum, default_value = "end")]
+    placement: PlacementArg,
+
     /// Print nothing but the passphrase (implied when -n is used)
     #[clap(short = 'q', long = "quiet")]
     quiet: bool,
 
-    /// Use the list of short words
-    #[clap(short = 's', long = "short")]
+    /// The built-in wordlist to use
+    #[clap(
+        short = 'l',
+        long = "list",
+        value_name = "name",
+        default_value = "eff-large",
+        value_parser = PossibleValuesParser::new(Wordlist::builtin_names()),
+        global = true
+    )]
+    list: String,
+
+    /// Same as --list eff-short, kept for compatibility
+    #[clap(
+        short = 's',
+        long = "short",
+        conflicts_with = "list",
+        global = true,
+        hide = true
+    )]
     short: bool,
+
+    /// Use the words from the given file, either one word per line or
+    /// dice-indexed like the EFF's lists
+    #[clap(
+        long = "wordlist",
+        value_name = "path",
+        conflicts_with_all = ["list", "short"],
+        global = true
+    )]
+    wordlist: Option<PathBuf>,
+
+    /// Cut words down to the fewest leading characters that tell them apart,
+    /// for lists designed for it like the EFF's short list #2, which can be
+    /// loaded with --wordlist. None of the built-in lists can be abbreviated
+    #[clap(long = "prefixes", global = true)]
+    prefixes: bool,
+
+    /// Never pick words that make passphrases ambiguous
+    #[clap(long = "exclude-ambiguous")]
+    exclude_ambiguous: bool,
+
+    /// Pick words by rolling physical dice and typing in the results
+    #[clap(long = "dice")]
+    dice: bool,
+
+    /// Log when each session of --dice happened and how often each face came
+    /// up to this file, to test the dice for bias across sessions
+    #[clap(long = "roll-log", value_name = "path", requires = "dice")]
+    roll_log: Option<PathBuf>,
+
+    /// Log the rolls themselves to the --roll-log too. INSECURE: anyone who
+    /// reads the log can recover the passphrases they picked
+    #[clap(long = "log-rolls", requires = "roll_log")]
+    log_rolls: bool,
+
+    /// Pick words by flipping a coin and typing in heads (H) and tails (T)
+    #[clap(long = "coins", conflicts_with_all = ["dice", "cards"])]
+    coins: bool,
+
+    /// Pick words by dealing cards from a shuffled deck and typing them in,
+    /// e.g. AS 7H KD 10C
+    #[clap(long = "cards", conflicts_with = "dice")]
+    cards: bool,
+
+    /// Where randomness comes from
+    #[clap(
+        long = "rng",
+        value_name = "source",
+        value_enum,
+        default_value = "thread"
+    )]
+    rng: RngArg,
+
+    /// Read randomness from this file or device, e.g. /dev/hwrng. The startup
+    /// health test alone takes 1024 bytes, so a file needs more than that
+    #[clap(long = "rng-file", value_name = "path", conflicts_with = "rng")]
+    rng_file: Option<PathBuf>,
+
+    /// INSECURE: derive all randomness from this hex string, so that the same
+    /// seed always gives the same passphrases. Only for tests and examples
+    #[clap(
+        long = "seed",
+        value_name = "hex",
+        value_parser = parse_seed,
+        conflicts_with_all = ["rng", "rng_file", "dice", "coins", "cards"]
+    )]
+    seed: Option<Seed>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum CaseArg {
+    /// all words in lowercase
+    Lower,
+    /// Every Word Capitalized
+    Title,
+    /// firstWordLowercaseTheRestCapitalized
+    Camel,
+    /// ALL WORDS IN UPPERCASE
+    Upper,
+    /// all lowercase, except for One random word
+    RandomWord,
+}
+
+impl From<CaseArg> for Case {
+    fn from(case: CaseArg) -> Self {
+        match case {
+            CaseArg::Lower => Case::Lower,
+            CaseArg::Title => Case::Title,
+            CaseArg::Camel => Case::Camel,
+            CaseArg::Upper => Case::Upper,
+            CaseArg::RandomWord => Case::RandomWord,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum PlacementArg {
+    /// After the last word
+    End,
+    /// At the end of random words
+    BetweenWords,
+    /// At random positions, even within words
+    Anywhere,
+}
+
+impl From<PlacementArg> for Placement {
+    fn from(placement: PlacementArg) -> Self {
+        match placement {
+            PlacementArg::End => Placement::End,
+            PlacementArg::BetweenWords => Placement::BetweenWords,
+            PlacementArg::Anywhere => Placement::Anywhere,
+        }
+    }
+}
+
+/// The bytes of a --seed.
+#[derive(Clone)]
+struct Seed(Vec<u8>);
+
+#[derive(Clone, Copy, ValueEnum)]
+enum RngArg {
+    /// The thread-local RNG, seeded from the operating system's
+    Thread,
+    /// The operating system's RNG
+    Os,
+    /// A ChaCha20 CSPRNG seeded from the operating system's RNG
+    Chacha20,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Print the dice rolls that pick each word of a passphrase
+    Rolls {
+        /// The passphrase, its words separated by the delimiter
+        passphrase: String,
+    },
+
+    /// Check how many words of an existing passphrase are in the wordlist,
+    /// and how much entropy that amounts to
+    Check {
+        /// The passphrase, its words separated by the delimiter
+        passphrase: String,
+
+        /// Fail unless the passphrase has at least this many bits of entropy
+        #[clap(long = "min-bits", value_name = "n", value_parser = parse_bits)]
+        min_bits: Option<f64>,
+    },
+
+    /// Describe the built-in wordlists, and the one given with --wordlist,
+    /// or work with wordlists
+    Lists {
+        #[command(subcommand)]
+        command: Option<ListsCommand>,
+    },
+}
+
+#[derive(Subcommand)]
+enum ListsCommand {
+    /// Check a wordlist for duplicates, ambiguous words and other problems
+    Lint {
+        /// The name of a built-in list, or the path to a wordlist file
+        list: String,
+    },
+
+    /// Check that the built-in wordlists match the files they were taken from
+    Verify,
+
+    /// Build a dice-indexed wordlist from the most frequent words of text
+    /// files, and print it
+    Build(BuildArgs),
+}
+
+#[derive(Args)]
+struct BuildArgs {
+    /// The text files to count words in
+    #[clap(required = true)]
+    corpus: Vec<PathBuf>,
+
+    /// Leave out words shorter than this many characters
+    #[clap(long = "min-length", value_name = "n", default_value = "3")]
+    min_length: usize,
+
+    /// Leave out words longer than this many characters
+    #[clap(long = "max-length", value_name = "n", default_value = "9")]
+    max_length: usize,
+
+    /// Leave out words with characters not in this set
+    #[clap(long = "charset", value_name = "chars", value_parser = parse_charset)]
+    charset: Option<String>,
+
+    /// Leave out the words in this file, e.g. profanity
+    #[clap(long = "blocklist", value_name = "path")]
+    blocklist: Option<PathBuf>,
+
+    /// Leave out words fewer than this many single character edits away
+    /// from a more frequent word
+    #[clap(long = "min-edit-distance", value_name = "n", default_value = "1")]
+    min_edit_distance: usize,
+
+    /// Build a list for this many dice per word, instead of as many as
+    /// there are words for
+    #[clap(long = "dice-per-word", value_name = "n")]
+    dice_per_word: Option<u32>,
 }
 
 impl Spiceware {
-    fn main(self) {
+    fn main(self) -> Result<(), Error> {
+        match &self.command {
+            Some(Command::Rolls { passphrase }) => return self.rolls(passphrase),
+            Some(Command::Check {
+                passphrase,
+                min_bits,
+            }) => return self.check(passphrase, *min_bits),
+            Some(Command::Lists {
+                command: Some(ListsCommand::Lint { list }),
+            }) => return lint(list),
+            Some(Command::Lists {
+                command: Some(ListsCommand::Verify),
+            }) => return verify(),
+            Some(Command::Lists {
+                command: Some(ListsCommand::Build(args)),
+            }) => return build(args),
+            Some(Command::Lists { command: None }) => return self.lists(),
+            None => {}
+        }
+
         if self.num_passwords > 1 || self.quiet {
-            self.batch_mode();
+            self.batch_mode()
         } else {
-            self.verbose_mode();
+            self.verbose_mode()
         }
     }
 
-    fn batch_mode(self) {
+    fn batch_mode(self) -> Result<(), Error> {
+        let mut generator = self.generator()?;
+        let logged = self.logged_rolls()?;
+        let mut entered = Entered::default();
         for _ in 0..self.num_passwords {
-            let passphrase = self.gen_passphrase();
+            let passphrase = self.passphrase(&mut generator, &mut entered)?;
             println!("{}", passphrase);
         }
+
+        self.test_rolls(&entered.rolls, logged)
     }
 
-    fn verbose_mode(self) {
-        let (power_of_ten, overflowed) = match self.possible_combinations() {
-            Some(combs) => (combs.ilog10(), false),
-            None => (usize::MAX.ilog10(), true),
-        };
+    fn verbose_mode(self) -> Result<(), Error> {
+        let mut generator = self.generator()?;
+        let entropy = generator.entropy();
+        let logged = self.logged_rolls()?;
 
-        let passphrase = self.gen_passphrase();
+        let ambiguous = generator.ambiguous_words();
+        if !ambiguous.is_empty() && !self.exclude_ambiguous {
+            let examples: Vec<&str> = ambiguous.iter().take(4).copied().collect();
+            let more = if ambiguous.len() > examples.len() {
+                ", ..."
+            } else {
+                ""
+            };
+            let (count, verb) = match ambiguous.len() {
+                1 => (String::from("1 word"), "makes"),
+                n => (format!("{n} words"), "make"),
+            };
+            eprintln!(
+                "warning: with this delimiter, {count} ({}{more}) {verb} passphrases ambiguous and won't count towards the entropy; --exclude-ambiguous never picks them\n",
+                examples.join(", "),
+            );
+        }
 
-        let qualifier = if overflowed { "over" } else { "about" };
+        let mut entered = Entered::default();
+        let passphrase = self.passphrase(&mut generator, &mut entered)?;
 
         println!("Your password is:\n");
         println!("\t{}\n", passphrase);
-        println!("This password is one of {qualifier} 10^{power_of_ten} possible combinations.");
+        println!(
+            "This password is one of {} possible combinations ({entropy} of entropy).",
+            entropy.combinations()
+        );
+        println!(
+            "Its words are from the {} wordlist.",
+            generator.wordlist().info().name()
+        );
+        if self.prefixes {
+            println!(
+                "Its words are abbreviated to their first {} characters.",
+                generator.wordlist().prefix_len()
+            );
+        }
+
+        self.test_rolls(&entered.rolls, logged)
+    }
+
+    /// The sessions in the --roll-log so far, read before rolling so that a
+    /// broken log doesn't waste any rolls.
+    fn logged_rolls(&self) -> Result<Option<Vec<Session>>, Error> {
+        if self.log_rolls {
+            eprintln!("warning: --log-rolls keeps the rolls in the --roll-log; anyone who reads it can recover the passphrases they picked");
+        }
+
+        self.roll_log.as_ref().map(dice::read_log).transpose()
+    }
+
+    /// Warn if the rolls of this session, or all logged ones, look biased,
+    /// then log this session.
+    fn test_rolls(&self, rolls: &[u8], logged: Option<Vec<Session>>) -> Result<(), Error> {
+        if !self.dice {
+            return Ok(());
+        }
+
+        let session = if self.log_rolls {
+            Session::with_rolls(rolls)
+        } else {
+            Session::new(rolls)
+        };
+        warn_if_suspicious(session.tally(), "these");
+
+        if let (Some(path), Some(logged)) = (&self.roll_log, logged) {
+            if !rolls.is_empty() {
+                session.append_to_log(path)?;
+            }
+
+            let mut history = *session.tally();
+            for logged in &logged {
+                history.merge(logged.tally());
+            }
+            warn_if_suspicious(&history, "all logged");
+        }
+
+        Ok(())
+    }
+
+    fn rolls(&self, passphrase: &str) -> Result<(), Error> {
+        let generator = self.generator()?;
+        let words = generator.split(passphrase)?;
+        let rolls = words
+            .iter()
+            .map(|word| generator.wordlist().rolls_for_word(word))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        for (word, rolls) in words.iter().zip(rolls) {
+            println!("{word} -> {}", dice::format_rolls(&rolls));
+        }
+
+        Ok(())
+    }
+
+    fn check(&self, passphrase: &str, min_bits: Option<f64>) -> Result<(), Error> {
+        let check = self.generator()?.check(passphrase)?;
+        let entropy = check.entropy();
+        let total = check.matched().len() + check.unmatched().len();
+
+        println!(
+            "{} of {total} words are in the wordlist.",
+            check.matched().len()
+        );
+        if !check.unmatched().is_empty() {
+            println!("Not in the wordlist: {}", check.unmatched().join(" "));
+        }
+        println!(
+            "If its listed words were picked at random, this password is one of {} possible combinations ({entropy} of entropy).",
+            entropy.combinations()
+        );
+
+        match min_bits {
+            Some(bits) if entropy.bits() < bits => Err(Error::TooWeak(format!(
+                "{entropy} of entropy, but at least {bits} bits are required"
+            ))),
+            _ => Ok(()),
+        }
     }
 
-    fn wordlist(&self) -> &[&str] {
-        if self.short {
-            &SHORT_WORDS
+    fn lists(&self) -> Result<(), Error> {
+        let mut wordlists: Vec<Wordlist> = Wordlist::builtin_names()
+            .filter_map(Wordlist::builtin)
+            .collect();
+        if let Some(path) = &self.wordlist {
+            wordlists.push(Wordlist::from_file(path)?);
+        }
+
+        for (i, wordlist) in wordlists.iter().enumerate() {
+            if i > 0 {
+                println!();
+            }
+            print_info(wordlist.info());
+        }
+
+        Ok(())
+    }
+
+    fn passphrase(
+        &self,
+        generator: &mut Generator<Rng>,
+        entered: &mut Entered,
+    ) -> Result<Passphrase, Error> {
+        if self.dice {
+            read_rolls(generator, &mut entered.rolls)
+        } else if self.coins {
+            read_pool(generator, Tokens::Flips, &mut entered.pool)
+        } else if self.cards {
+            read_pool(generator, Tokens::Cards, &mut entered.pool)
         } else {
-            &WORDS
+            generator.generate()
         }
     }
 
-    fn worst_case_passphrase_size(&self) -> usize {
-        let word_size = if self.short {
-            short_words::MAX_SIZE
+    fn wordlist(&self) -> Result<Wordlist, Error> {
+        let wordlist = if let Some(path) = &self.wordlist {
+            Wordlist::from_file(path)?
         } else {
-            words::MAX_SIZE
+            let name = if self.short { "eff-short" } else { &self.list };
+            Wordlist::builtin(name).expect("clap only accepts built-in names")
+        };
+
+        if !self.prefixes {
+            return Ok(wordlist);
+        }
+
+        let prefix_len = wordlist.prefix_len();
+        let longest = wordlist.iter().map(|word| word.chars().count()).max();
+        if longest.is_some_and(|longest| prefix_len >= longest) {
+            return Err(Error::InvalidWordlist(format!(
+                "the words of the {} wordlist can't be abbreviated, telling them apart takes all of their characters; pass a list designed for it, like the EFF's short list #2, with --wordlist",
+                wordlist.info().name()
+            )));
+        }
+
+        Ok(wordlist.abbreviated())
+    }
+
+    fn rng(&self) -> Result<Rng, Error> {
+        let rng: Rng = if let Some(seed) = &self.seed {
+            eprintln!("warning: passphrases from --seed can be reproduced by anyone who knows the seed; never use them for anything real");
+            Box::new(source::seeded(&seed.0))
+        } else if let Some(path) = &self.rng_file {
+            Box::new(source::FileSource::open(path)?)
+        } else {
+            match self.rng {
+                RngArg::Thread => Box::new(rand::thread_rng()),
+                RngArg::Os => Box::new(source::os()),
+                RngArg::Chacha20 => Box::new(source::chacha20()?),
+            }
+        };
+
+        Ok(Box::new(HealthChecked::new(rng)))
+    }
+
+    fn generator(&self) -> Result<Generator<Rng>, Error> {
+        let generator = Generator::new(self.wordlist()?)
+            .rng(self.rng()?)
+            .delimiter(self.delimiter.as_str());
+        let generator = match &self.delimiter_set {
+            Some(set) => generator.delimiter_set(set),
+            None => generator,
+        };
+        let generator = generator
+            .case(self.case.into())
+            .digits(self.digits)
+            .symbols(self.symbols)
+            .symbol_set(&self.symbol_set)
+            .placement(self.placement.into())
+            .exclude_ambiguous(self.exclude_ambiguous);
+
+        let ambiguous = generator.ambiguous_words().len();
+        if self.exclude_ambiguous && ambiguous == generator.wordlist().len() {
+            return Err(Error::InvalidDelimiter(String::from(
+                "every word makes passphrases ambiguous",
+            )));
+        }
+
+        let num_words = match self.bits {
+            Some(bits) => generator
+                .num_words_for_bits(bits)
+                .ok_or_else(|| Error::TooWeak(format!("no number of words reaches {bits} bits")))?
+                .max(1),
+            None => self.num_words,
+        };
+
+        Ok(generator.num_words(num_words))
+    }
+}
+
+fn print_info(info: &WordlistInfo) {
+    let unknown = "unknown";
+    let dice = match info.dice_per_word() {
+        Some(num_dice) => format!("{num_dice} dice per word"),
+        None => String::from("can't be used with dice"),
+    };
+
+    println!("{}", info.name());
+    println!("  words:        {} ({dice})", info.len());
+    println!("  longest word: {} bytes", info.max_size());
+    println!("  language:     {}", info.language().unwrap_or(unknown));
+    println!("  license:      {}", info.license().unwrap_or(unknown));
+    println!("  source:       {}", info.source().unwrap_or(unknown));
+}
+
+fn lint(list: &str) -> Result<(), Error> {
+    let report = match Wordlist::builtin(list) {
+        Some(wordlist) => lint::lint_wordlist(&wordlist),
+        None => lint::lint_file(list)?,
+    };
+
+    println!(
+        "{list}: {} words, the largest of which is {} bytes long",
+        report.len(),
+        report.max_size()
+    );
+    for finding in report.findings() {
+        println!("{finding}");
+    }
+
+    if report.has_errors() {
+        return Err(Error::InvalidWordlist(format!(
+            "{list} didn't pass linting"
+        )));
+    }
+
+    Ok(())
+}
+
+fn verify() -> Result<(), Error> {
+    let mut mismatched = 0;
+    let mut unverified = 0;
+    for name in Wordlist::builtin_names() {
+        let wordlist = Wordlist::builtin(name).expect("name is built-in");
+        let actual = wordlist.sha256()?;
+        let Some(expected) = Wordlist::builtin_sha256(name) else {
+            println!("{name}: UNVERIFIED, SHA-256 {actual}, no upstream hash is pinned");
+            unverified += 1;
+            continue;
         };
 
-        let delimiter_size = self.delimiter.len() * (self.num_words as usize - 1);
-        self.num_words as usize * word_size + delimiter_size
+        if actual == expected {
+            println!("{name}: OK, SHA-256 {actual}");
+        } else {
+            println!("{name}: MISMATCH, SHA-256 {actual}, expected {expected}");
+            mismatched += 1;
+        }
     }
 
-    fn possible_combinations(&self) -> Option<usize> {
-        self.wordlist().len().checked_pow(self.num_words)
+    if mismatched > 0 {
+        return Err(Error::InvalidWordlist(format!(
+            "{mismatched} built-in lists don't match their upstream files"
+        )));
+    }
+    if unverified > 0 {
+        return Err(Error::InvalidWordlist(format!(
+            "{unverified} built-in lists can't be verified without a pinned upstream hash"
+        )));
     }
 
-    fn get_word(&self) -> &str {
-        let mut rng = rand::thread_rng();
-        let wordlist = self.wordlist();
-        let index = rng.gen_range(0..wordlist.len());
-        wordlist[index]
+    Ok(())
+}
+
+fn build(args: &BuildArgs) -> Result<(), Error> {
+    let mut corpus = Corpus::new();
+    for path in &args.corpus {
+        corpus.add_file(path)?;
     }
 
-    fn gen_passphrase(&self) -> String {
-        let mut passphrase = String::with_capacity(self.worst_case_passphrase_size());
-        for _ in 0..self.num_words - 1 {
-            passphrase.push_str(self.get_word());
-            passphrase.push_str(&self.delimiter);
+    let builder = ListBuilder::new()
+        .lengths(args.min_length..=args.max_length)
+        .min_edit_distance(args.min_edit_distance);
+    let builder = match &args.charset {
+        Some(charset) => builder.charset(charset),
+        None => builder,
+    };
+    let builder = match &args.blocklist {
+        Some(path) => builder.blocklist_file(path)?,
+        None => builder,
+    };
+    let builder = match args.dice_per_word {
+        Some(num_dice) => builder.num_dice(num_dice),
+        None => builder,
+    };
+
+    let wordlist = builder.build(&corpus)?;
+    eprintln!(
+        "Picked {} of {} distinct words, for {} dice per word.",
+        wordlist.len(),
+        corpus.len(),
+        wordlist.dice_per_word()?
+    );
+    print!("{}", wordlist.to_dice_file()?);
+
+    Ok(())
+}
+
+/// Prompt for dice rolls on stderr and read them from stdin, one line per word,
+/// until every word of a passphrase has been rolled. Accepted rolls are
+/// also appended to `all_rolls`.
+fn read_rolls(generator: &Generator<Rng>, all_rolls: &mut Vec<u8>) -> Result<Passphrase, Error> {
+    let num_dice = generator.dice_per_word()?;
+
+    let num_words = generator.word_count();
+    let mut lines = io::stdin().lock().lines();
+    let mut rolls = Vec::with_capacity(num_dice as usize * num_words as usize);
+    for word in 1..=num_words {
+        loop {
+            eprint!("Roll {num_dice} dice for word {word} of {num_words}: ");
+            io::stderr().flush()?;
+
+            let Some(line) = lines.next() else {
+                return Err(
+                    io::Error::new(io::ErrorKind::UnexpectedEof, "ran out of dice rolls").into(),
+                );
+            };
+
+            match dice::parse_rolls(&line?).and_then(|word_rolls| {
+                generator.index_for_rolls(&word_rolls)?;
+                Ok(word_rolls)
+            }) {
+                Ok(word_rolls) => {
+                    all_rolls.extend(&word_rolls);
+                    rolls.extend(word_rolls);
+                    break;
+                }
+                Err(err) => eprintln!("{err}, try again"),
+            }
         }
+    }
 
-        passphrase.push_str(self.get_word());
+    generator.passphrase_from_rolls(&rolls)
+}
 
-        passphrase
+/// Warn on stderr if the dice that rolled `tally` may not be fair.
+fn warn_if_suspicious(tally: &Tally, which: &str) {
+    if !tally.is_suspicious() {
+        return;
     }
+
+    let counts: Vec<String> = tally.counts().iter().map(u64::to_string).collect();
+    eprintln!(
+        "warning: {which} {} rolls are unlikely from a fair die (faces 1 to 6 came up {} times, chi-squared {:.1} > {CRITICAL_VALUE}); check the dice, and that the rolls are real",
+        tally.total(),
+        counts.join(", "),
+        tally.chi_squared(),
+    );
 }
 
-fn main() {
+/// What's been entered by hand so far, kept across the passphrases of a run so
+/// cards stay dealt and leftover randomness carries over.
+#[derive(Default)]
+struct Entered {
+    rolls: Vec<u8>,
+    pool: Pool,
+}
+
+/// What --coins or --cards reads.
+#[derive(Clone, Copy)]
+enum Tokens {
+    Flips,
+    Cards,
+}
+
+/// Prompt for coin flips or cards on stderr and read them from stdin, as many
+/// lines as it takes, until every word of a passphrase has been drawn from
+/// `pool`.
+fn read_pool(
+    generator: &Generator<Rng>,
+    tokens: Tokens,
+    pool: &mut Pool,
+) -> Result<Passphrase, Error> {
+    let num_words = generator.word_count();
+    let len = generator.wordlist().len();
+    let mut lines = io::stdin().lock().lines();
+    let mut indices = Vec::with_capacity(num_words as usize);
+    for word in 1..=num_words {
+        loop {
+            if let Some(index) = generator.index_from_pool(pool)? {
+                indices.push(index);
+                break;
+            }
+
+            let (needed, what) = match tokens {
+                Tokens::Flips => (pool.flips_needed(len).max(1), "coin flips"),
+                Tokens::Cards => (pool.cards_needed(len).max(1), "cards"),
+            };
+            eprint!("Enter at least {needed} more {what} for word {word} of {num_words}: ");
+            io::stderr().flush()?;
+
+            let Some(line) = lines.next() else {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    format!("ran out of {what}"),
+                )
+                .into());
+            };
+
+            let line = line?;
+            let added = match tokens {
+                Tokens::Flips => pool.add_flips(&line),
+                Tokens::Cards => pool.add_cards(&line),
+            };
+            if let Err(err) = added {
+                eprintln!("{err}, try again");
+            }
+        }
+    }
+
+    generator.passphrase_from_indices(&indices)
+}
+
+fn parse_bits(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(bits) if (0.0..=MAX_BITS).contains(&bits) => Ok(bits),
+        Ok(_) => Err(format!("must be between 0 and {MAX_BITS}")),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+fn parse_seed(s: &str) -> Result<Seed, String> {
+    if let Some(c) = s.chars().find(|c| !c.is_ascii_hexdigit()) {
+        return Err(format!("'{c}' is not a hex digit"));
+    }
+    if s.is_empty() || !s.len().is_multiple_of(2) {
+        return Err(String::from("must be an even number of hex digits"));
+    }
+
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).map_err(|err| err.to_string()))
+        .collect::<Result<_, _>>()
+        .map(Seed)
+}
+
+fn parse_charset(s: &str) -> Result<String, String> {
+    if s.is_empty() {
+        Err(String::from("must contain at least one character"))
+    } else {
+        Ok(String::from(s))
+    }
+}
+
+fn main() -> ExitCode {
     let args = Spiceware::parse();
-    args.main();
+    match args.main() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("spiceware: {err}");
+            ExitCode::FAILURE
+        }
+    }
 }
@@ -0,0 +1,48 @@
+/// Words packed back to back into a single string, along with where each of
+/// them starts, as the embedded lists are stored.
+#[derive(Debug, Clone, Copy)]
+pub struct PackedWords {
+    data: &'static str,
+    /// Where every word starts in `data`, followed by where the last one ends.
+    offsets: &'static [u32],
+    /// Size, in bytes, of the largest word, worked out when the words were
+    /// packed.
+    max_size: usize,
+}
+
+impl PackedWords {
+    pub(crate) const fn new(data: &'static str, offsets: &'static [u32], max_size: usize) -> Self {
+        Self {
+            data,
+            offsets,
+            max_size,
+        }
+    }
+
+    /// The number of words.
+    pub fn len(&self) -> usize {
+        self.offsets.len().saturating_sub(1)
+    }
+
+    /// Whether there are no words at all.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Size, in bytes, of the largest word.
+    pub fn max_size(&self) -> usize {
+        self.max_size
+    }
+
+    /// The word at `index`, if there is one.
+    pub fn get(&self, index: usize) -> Option<&'static str> {
+        let start = *self.offsets.get(index)? as usize;
+        let end = *self.offsets.get(index + 1)? as usize;
+        Some(&self.data[start..end])
+    }
+
+    /// Iterate over all words, in order.
+    pub fn iter(&self) -> impl Iterator<Item = &'static str> + '_ {
+        (0..self.len()).filter_map(move |index| self.get(index))
+    }
+}
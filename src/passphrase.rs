@@ -0,0 +1,41 @@
+use std::fmt;
+
+/// A passphrase produced by a [`Generator`](crate::Generator).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Passphrase {
+    inner: String,
+}
+
+impl Passphrase {
+    pub(crate) fn new(inner: String) -> Self {
+        Self { inner }
+    }
+
+    /// The passphrase as a string slice.
+    pub fn as_str(&self) -> &str {
+        &self.inner
+    }
+
+    /// Consume the passphrase, returning the underlying string.
+    pub fn into_string(self) -> String {
+        self.inner
+    }
+}
+
+impl AsRef<str> for Passphrase {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl fmt::Display for Passphrase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.inner)
+    }
+}
+
+impl From<Passphrase> for String {
+    fn from(passphrase: Passphrase) -> Self {
+        passphrase.into_string()
+    }
+}
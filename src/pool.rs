@@ -0,0 +1,310 @@
+//! Picking words with coin flips or shuffled playing cards instead of a
+//! computer's RNG.
+//!
+//! Both are collected into a [`Pool`]: a number known to be uniformly random
+//! within a range, which grows with every flip or card. Words are drawn from
+//! it by rejection sampling, so that every word is exactly as likely as any
+//! other, and whatever a rejected draw leaves over is kept for the next one.
+
+use crate::{Entropy, Error};
+use num_bigint::BigUint;
+
+/// The number of cards in a deck.
+pub const DECK_SIZE: usize = 52;
+
+const RANKS: [&str; 13] = [
+    "A", "2", "3", "4", "5", "6", "7", "8", "9", "10", "J", "Q", "K",
+];
+const SUITS: [char; 4] = ['S', 'H', 'D', 'C'];
+
+/// A uniformly random number below a known bound, gathered from coin flips or
+/// cards.
+#[derive(Debug, Clone)]
+pub struct Pool {
+    value: BigUint,
+    range: BigUint,
+    /// The cards of the current deck dealt so far, by index into the deck.
+    dealt: Vec<usize>,
+}
+
+impl Pool {
+    /// An empty pool, holding no randomness at all.
+    pub fn new() -> Self {
+        Self {
+            value: BigUint::from(0u32),
+            range: BigUint::from(1u32),
+            dealt: Vec::new(),
+        }
+    }
+
+    /// The number of bits of randomness in this pool.
+    pub fn bits(&self) -> f64 {
+        Entropy::from_combinations(self.range.clone()).bits()
+    }
+
+    /// Add coin flips like `"HTTH"`, `H` for heads and `T` for tails.
+    ///
+    /// Whitespace and commas between flips are ignored. Nothing is added
+    /// unless all flips are valid.
+    pub fn add_flips(&mut self, flips: &str) -> Result<(), Error> {
+        let flips: Vec<u32> = flips
+            .chars()
+            .filter(|c| !c.is_whitespace() && *c != ',')
+            .map(|c| match c.to_ascii_uppercase() {
+                'H' => Ok(0),
+                'T' => Ok(1),
+                _ => Err(Error::InvalidFlips(format!("'{c}' is neither H nor T"))),
+            })
+            .collect::<Result<_, _>>()?;
+
+        for flip in flips {
+            self.add(flip, 2);
+        }
+
+        Ok(())
+    }
+
+    /// Add the next cards dealt from a shuffled deck, like `"AS 7H KD 10C"`:
+    /// a rank (`A`, `2` to `10`, `J`, `Q` or `K`) followed by a suit (`S`, `H`,
+    /// `D` or `C`). `T` may stand in for `10`.
+    ///
+    /// Cards are dealt from the same deck, which can't repeat a card, until
+    /// all of its cards have been dealt; the next card starts a freshly
+    /// shuffled deck. Nothing is added unless all cards are valid.
+    pub fn add_cards(&mut self, cards: &str) -> Result<(), Error> {
+        let mut dealt = self.dealt.clone();
+        let mut symbols = Vec::new();
+        for card in cards.split(|c: char| c.is_whitespace() || c == ',') {
+            if card.is_empty() {
+                continue;
+            }
+
+            let index = parse_card(card)?;
+            if dealt.len() == DECK_SIZE {
+                dealt.clear();
+            }
+            if dealt.contains(&index) {
+                return Err(Error::InvalidCards(format!(
+                    "{card} was already dealt from this deck"
+                )));
+            }
+
+            // The card's position among those left in the deck.
+            let position = index - dealt.iter().filter(|&&other| other < index).count();
+            symbols.push((position as u32, (DECK_SIZE - dealt.len()) as u32));
+            dealt.push(index);
+        }
+
+        for (position, left) in symbols {
+            self.add(position, left);
+        }
+        self.dealt = dealt;
+
+        Ok(())
+    }
+
+    /// Add `value`, uniformly random below `range`.
+    fn add(&mut self, value: u32, range: u32) {
+        self.value = &self.value * range + value;
+        self.range *= range;
+    }
+
+    /// Draw a uniformly random number below `n`, or `None` if this pool needs
+    /// more flips or cards first.
+    pub fn draw(&mut self, n: usize) -> Option<usize> {
+        if n == 0 {
+            return None;
+        }
+
+        let n = BigUint::from(n);
+        while self.range >= n {
+            // Only the largest multiple of n below the range maps to every
+            // number equally often.
+            let accepted = &self.range / &n * &n;
+            if self.value < accepted {
+                let index = &self.value % &n;
+                self.value /= &n;
+                self.range = accepted / &n;
+                return usize::try_from(&index).ok();
+            }
+
+            // The value is uniformly random among the rejected ones.
+            self.value -= &accepted;
+            self.range -= accepted;
+        }
+
+        None
+    }
+
+    /// The smallest number of coin flips after which a number below `n` might
+    /// be drawn.
+    pub fn flips_needed(&self, n: usize) -> usize {
+        self.needed(n, std::iter::repeat(2))
+    }
+
+    /// The smallest number of cards after which a number below `n` might be
+    /// drawn.
+    pub fn cards_needed(&self, n: usize) -> usize {
+        let left = DECK_SIZE - self.dealt.len() % DECK_SIZE;
+        let decks = std::iter::repeat((1..=DECK_SIZE as u32).rev()).flatten();
+        let ranges = (1..=left as u32).rev().chain(decks);
+        self.needed(n, ranges)
+    }
+
+    fn needed(&self, n: usize, ranges: impl Iterator<Item = u32>) -> usize {
+        let n = BigUint::from(n);
+        let mut range = self.range.clone();
+        let mut needed = 0;
+        for symbol in ranges {
+            if range >= n {
+                break;
+            }
+            range *= symbol;
+            needed += 1;
+        }
+
+        needed
+    }
+}
+
+impl Default for Pool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The index of `card` in a deck sorted by suit, then rank.
+fn parse_card(card: &str) -> Result<usize, Error> {
+    let invalid = || Error::InvalidCards(format!("\"{card}\" is not a card, e.g. AS or 10H"));
+
+    let upper = card.to_ascii_uppercase();
+    let suit = upper.chars().last().ok_or_else(invalid)?;
+    let rank = match &upper[..upper.len() - suit.len_utf8()] {
+        "T" => "10",
+        rank => rank,
+    };
+
+    let suit = SUITS.iter().position(|&s| s == suit).ok_or_else(invalid)?;
+    let rank = RANKS.iter().position(|&r| r == rank).ok_or_else(invalid)?;
+    Ok(suit * RANKS.len() + rank)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Every card of a deck, in the order [`parse_card`] ranks them.
+    fn deck() -> Vec<String> {
+        SUITS
+            .iter()
+            .flat_map(|suit| RANKS.iter().map(move |rank| format!("{rank}{suit}")))
+            .collect()
+    }
+
+    /// The flips that add `value`, below `2^num_flips`, to a pool.
+    fn flips(value: u32, num_flips: u32) -> String {
+        (0..num_flips)
+            .rev()
+            .map(|bit| if value >> bit & 1 == 0 { 'H' } else { 'T' })
+            .collect()
+    }
+
+    #[test]
+    fn draws_are_below_n() {
+        for n in [2, 3, 6, 7, 1296, 7776] {
+            let mut pool = Pool::new();
+            for value in 0..200u32 {
+                pool.add_flips(&flips(value.wrapping_mul(2654435761) % 4096, 12))
+                    .unwrap();
+                while let Some(index) = pool.draw(n) {
+                    assert!(index < n);
+                }
+                assert!(pool.range < BigUint::from(n));
+            }
+        }
+    }
+
+    #[test]
+    fn drawing_from_one_takes_no_randomness() {
+        assert_eq!(Pool::new().draw(1), Some(0));
+        assert_eq!(Pool::new().draw(0), None);
+        assert_eq!(Pool::new().flips_needed(1), 0);
+    }
+
+    #[test]
+    fn draws_are_uniform() {
+        for n in [2, 3, 6, 7, 100, 1000] {
+            let mut counts = vec![0; n];
+            let mut rejected = 0;
+            for value in 0..1024 {
+                let mut pool = Pool::new();
+                pool.add_flips(&flips(value, 10)).unwrap();
+                match pool.draw(n) {
+                    Some(index) => counts[index] += 1,
+                    None => rejected += 1,
+                }
+            }
+
+            assert!(counts.iter().all(|&count| count == 1024 / n), "{n}");
+            assert_eq!(rejected, 1024 % n);
+        }
+    }
+
+    #[test]
+    fn a_deck_holds_52_factorial_orders() {
+        let mut pool = Pool::new();
+        pool.add_cards(&deck().join(" ")).unwrap();
+
+        let factorial = (1..=DECK_SIZE as u32).fold(BigUint::from(1u32), |f, i| f * i);
+        assert_eq!(pool.range, factorial);
+        assert!((pool.bits() - 225.58).abs() < 0.01);
+
+        // The last card of a deck adds nothing, as it's the only one left.
+        let mut pool = Pool::new();
+        pool.add_cards(&deck()[..51].join(" ")).unwrap();
+        let range = pool.range.clone();
+        pool.add_cards(&deck()[51]).unwrap();
+        assert_eq!(pool.range, range);
+    }
+
+    #[test]
+    fn ranks_cards_among_those_left() {
+        let mut pool = Pool::new();
+        pool.add_cards("2S").unwrap();
+        assert_eq!(
+            (pool.value.clone(), pool.range.clone()),
+            (1u32.into(), 52u32.into())
+        );
+
+        // The ace of spades is now the first of 51 cards left.
+        pool.add_cards("as").unwrap();
+        assert_eq!(
+            (pool.value.clone(), pool.range.clone()),
+            (51u32.into(), 2652u32.into())
+        );
+
+        assert_eq!(parse_card("10H").unwrap(), parse_card("th").unwrap());
+    }
+
+    #[test]
+    fn rejects_invalid_input_without_changing_the_pool() {
+        let mut pool = Pool::new();
+        pool.add_cards("AS 7H").unwrap();
+        let (value, range, dealt) = (pool.value.clone(), pool.range.clone(), pool.dealt.clone());
+
+        assert!(pool.add_cards("KD AS").is_err());
+        assert!(pool.add_cards("KD KD").is_err());
+        assert!(pool.add_cards("KD 1S").is_err());
+        assert!(pool.add_cards("KD X").is_err());
+        assert!(pool.add_flips("HTX").is_err());
+        assert_eq!((pool.value, pool.range, pool.dealt), (value, range, dealt));
+    }
+
+    #[test]
+    fn starts_a_new_deck_after_52_cards() {
+        let mut pool = Pool::new();
+        pool.add_cards(&deck().join(" ")).unwrap();
+        pool.add_cards("AS").unwrap();
+        assert_eq!(pool.dealt, [0]);
+    }
+}
@@ -0,0 +1,118 @@
+//! Where the randomness that picks words comes from.
+//!
+//! A [`Generator`](crate::Generator) accepts any [`RngCore`]. Besides the
+//! thread-local RNG it uses by default, this module offers the operating
+//! system's RNG, a ChaCha20 CSPRNG seeded from it, bytes read from a file or
+//! device such as `/dev/hwrng`, and a deterministic RNG for tests. Failures
+//! of a source, as reported by [`RngCore::try_fill_bytes`], abort generation
+//! with an error; [`HealthChecked`](crate::health::HealthChecked) makes a
+//! source fail when its output stops looking random.
+
+use crate::Error;
+use rand::rngs::OsRng;
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::io::{self, BufReader, Read};
+use std::path::{Path, PathBuf};
+
+/// The operating system's RNG, used through `getrandom` directly.
+pub fn os() -> OsRng {
+    OsRng
+}
+
+/// A ChaCha20 CSPRNG seeded from the operating system's RNG.
+pub fn chacha20() -> Result<ChaCha20Rng, Error> {
+    ChaCha20Rng::from_rng(OsRng).map_err(|err| Error::EntropySource(err.to_string()))
+}
+
+/// A ChaCha20 RNG whose output is entirely determined by `seed`, of any
+/// length, for reproducible test vectors.
+///
+/// Anyone who knows the seed can reproduce its passphrases, so never use them
+/// for anything real. Given the same seed, wordlist and options, a
+/// [`Generator`](crate::Generator) produces the same passphrases on every
+/// platform.
+pub fn seeded(seed: &[u8]) -> ChaCha20Rng {
+    ChaCha20Rng::from_seed(Sha256::digest(seed).into())
+}
+
+/// Random bytes read from a file or device, e.g. `/dev/hwrng`.
+///
+/// Running out of bytes is an error, so a regular file only lasts as long as
+/// its contents.
+#[derive(Debug)]
+pub struct FileSource {
+    path: PathBuf,
+    reader: BufReader<File>,
+}
+
+impl FileSource {
+    /// Read random bytes from the file or device at `path`.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let file = File::open(path)
+            .map_err(|err| io::Error::new(err.kind(), format!("{}: {err}", path.display())))?;
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            reader: BufReader::new(file),
+        })
+    }
+
+    /// The path bytes are read from.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl RngCore for FileSource {
+    fn next_u32(&mut self) -> u32 {
+        rand_core::impls::next_u32_via_fill(self)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        rand_core::impls::next_u64_via_fill(self)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        if let Err(err) = self.try_fill_bytes(dest) {
+            panic!("{err}");
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.reader.read_exact(dest).map_err(|err| {
+            let err = match err.kind() {
+                io::ErrorKind::UnexpectedEof => io::Error::new(
+                    err.kind(),
+                    format!("ran out of bytes in {}", self.path.display()),
+                ),
+                _ => io::Error::new(err.kind(), format!("{}: {err}", self.path.display())),
+            };
+            rand::Error::new(err)
+        })
+    }
+}
+
+/// A uniformly random number below `n`, which must not be zero.
+///
+/// Draws eight bytes at a time, read as a little-endian number, and rejects
+/// the few values that would favor some results over others. Unlike `rand`'s
+/// own sampling, this depends on nothing but the bytes `rng` produces.
+pub(crate) fn uniform<R: RngCore + ?Sized>(rng: &mut R, n: usize) -> Result<usize, Error> {
+    let n = n as u64;
+    // 2^64 mod n values at the top would be picked more often than the rest.
+    let rejected = (u64::MAX % n + 1) % n;
+    loop {
+        let mut bytes = [0; 8];
+        rng.try_fill_bytes(&mut bytes)
+            .map_err(|err| Error::EntropySource(err.to_string()))?;
+
+        let value = u64::from_le_bytes(bytes);
+        if value <= u64::MAX - rejected {
+            return Ok((value % n) as usize);
+        }
+    }
+}
@@ -0,0 +1,540 @@
+use crate::{dice, short_words, words, Error, PackedWords, WordlistInfo};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// A list of words that passphrases are made up of.
+#[derive(Debug, Clone)]
+pub struct Wordlist {
+    words: Words,
+    info: WordlistInfo,
+}
+
+#[derive(Debug, Clone)]
+enum Words {
+    Packed(PackedWords),
+    Owned(Vec<String>),
+}
+
+impl Wordlist {
+    /// The EFF's large wordlist.
+    pub fn large() -> Self {
+        Self::builtin("eff-large").expect("eff-large is built-in")
+    }
+
+    /// The EFF's short wordlist.
+    pub fn short() -> Self {
+        Self::builtin("eff-short").expect("eff-short is built-in")
+    }
+
+    /// The names of the built-in lists, see [`Wordlist::builtin`].
+    pub fn builtin_names() -> impl Iterator<Item = &'static str> {
+        BUILTIN.iter().map(|builtin| builtin.name)
+    }
+
+    /// The built-in list called `name`, if there is one.
+    pub fn builtin(name: &str) -> Option<Self> {
+        let builtin = find_builtin(name)?;
+        let words = *builtin.words;
+        Some(Self {
+            words: Words::Packed(words),
+            info: WordlistInfo {
+                name: String::from(builtin.name),
+                source: Some(String::from(builtin.source)),
+                license: Some(String::from(builtin.license)),
+                language: Some(String::from(builtin.language)),
+                len: words.len(),
+                dice_per_word: dice::dice_for(words.len()),
+                max_size: words.max_size(),
+            },
+        })
+    }
+
+    /// The SHA-256 hash, in hex, of the upstream file the built-in list called
+    /// `name` was taken from, to compare [`Wordlist::sha256`] against.
+    ///
+    /// `None` if there's no such list, or if the hash of its upstream file
+    /// hasn't been pinned yet, in which case it can't be verified.
+    pub fn builtin_sha256(name: &str) -> Option<&'static str> {
+        find_builtin(name).and_then(|builtin| builtin.sha256)
+    }
+
+    /// Load a wordlist from the file at `path`.
+    ///
+    /// See [`Wordlist::parse`] for the accepted formats. The list is named
+    /// after the file.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let mut wordlist = Self::parse(&read_file(path)?)?;
+        if let Some(name) = path.file_name() {
+            wordlist.info.name = name.to_string_lossy().into_owned();
+        }
+        wordlist.info.source = Some(path.display().to_string());
+
+        Ok(wordlist)
+    }
+
+    /// Parse a wordlist from `text`, naming it `custom`.
+    ///
+    /// Two formats are accepted: plain lists with one word per line, and
+    /// dice-indexed lists like the EFF's, where every line holds a sequence
+    /// of dice rolls followed by whitespace and a word, e.g. `11111\tabacus`.
+    /// Blank lines are ignored. Dice-indexed lists must cover every possible
+    /// roll exactly once; their words are ordered by roll. Lists that come as
+    /// PGP clear-signed messages, like the original Diceware list, are read
+    /// without their signature, which isn't checked.
+    pub fn parse(text: &str) -> Result<Self, Error> {
+        Self::from_words(parse_words(text)?)
+    }
+
+    pub(crate) fn from_words(words: Vec<&str>) -> Result<Self, Error> {
+        if words.len() < 2 {
+            return Err(invalid(String::from("fewer than two words")));
+        }
+
+        let mut seen = HashSet::with_capacity(words.len());
+        for word in &words {
+            if !seen.insert(*word) {
+                return Err(invalid(format!("duplicate word \"{word}\"")));
+            }
+        }
+
+        let info = WordlistInfo {
+            name: String::from("custom"),
+            source: None,
+            license: None,
+            language: None,
+            len: words.len(),
+            dice_per_word: dice::dice_for(words.len()),
+            max_size: words.iter().map(|word| word.len()).max().unwrap_or(0),
+        };
+        let words = words.into_iter().map(String::from).collect();
+
+        Ok(Self {
+            words: Words::Owned(words),
+            info,
+        })
+    }
+
+    /// What's known about this list.
+    pub fn info(&self) -> &WordlistInfo {
+        &self.info
+    }
+
+    /// The number of words in this list.
+    pub fn len(&self) -> usize {
+        match &self.words {
+            Words::Packed(words) => words.len(),
+            Words::Owned(words) => words.len(),
+        }
+    }
+
+    /// Whether this list contains no words at all.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Size, in bytes, of the largest word in this list.
+    pub fn max_size(&self) -> usize {
+        self.info.max_size
+    }
+
+    /// The word at `index`, if there is one.
+    pub fn get(&self, index: usize) -> Option<&str> {
+        match &self.words {
+            Words::Packed(words) => words.get(index),
+            Words::Owned(words) => words.get(index).map(String::as_str),
+        }
+    }
+
+    /// The smallest number of leading characters that tells every word of
+    /// this list apart from the others, e.g. 3 for the EFF's short list #2,
+    /// which isn't built in but can be loaded with [`Wordlist::from_file`].
+    /// For lists not designed for it, this is often the length of the longest
+    /// word.
+    pub fn prefix_len(&self) -> usize {
+        let mut words: Vec<&str> = self.iter().collect();
+        words.sort_unstable();
+        prefix_len(&words)
+    }
+
+    /// This list with every word cut down to its first
+    /// [`prefix_len`](Wordlist::prefix_len) characters.
+    ///
+    /// The words stay in the same order, so the same dice rolls pick them,
+    /// and the list keeps its name.
+    pub fn abbreviated(&self) -> Self {
+        let prefix_len = self.prefix_len();
+        let words = self
+            .iter()
+            .map(|word| match word.char_indices().nth(prefix_len) {
+                Some((end, _)) => &word[..end],
+                None => word,
+            })
+            .collect();
+
+        let mut abbreviated =
+            Self::from_words(words).expect("prefixes are as distinct as the words");
+        abbreviated.info = WordlistInfo {
+            max_size: abbreviated.info.max_size,
+            ..self.info.clone()
+        };
+
+        abbreviated
+    }
+
+    /// The number of dice rolls that pick a word from this list.
+    ///
+    /// Fails unless the size of this list is a power of six.
+    pub fn dice_per_word(&self) -> Result<u32, Error> {
+        dice::dice_for(self.len()).ok_or_else(|| {
+            Error::InvalidRolls(format!(
+                "a list of {} words can't be used with dice",
+                self.len()
+            ))
+        })
+    }
+
+    /// The index of the word picked by `rolls`, one roll per die.
+    pub fn index_for_rolls(&self, rolls: &[u8]) -> Result<usize, Error> {
+        let num_dice = self.dice_per_word()?;
+
+        if rolls.len() != num_dice as usize {
+            return Err(Error::InvalidRolls(format!(
+                "expected {num_dice} rolls, got {}",
+                rolls.len()
+            )));
+        }
+
+        if let Some(roll) = rolls.iter().find(|roll| !(1..=6).contains(*roll)) {
+            return Err(Error::InvalidRolls(format!("{roll} is not a roll of a d6")));
+        }
+
+        Ok(dice::index_for(rolls))
+    }
+
+    /// The word picked by `rolls`, one roll per die.
+    pub fn word_for_rolls(&self, rolls: &[u8]) -> Result<&str, Error> {
+        let index = self.index_for_rolls(rolls)?;
+        Ok(self.get(index).expect("index is within bounds"))
+    }
+
+    /// The index of `word` in this list, if it's there.
+    pub fn position(&self, word: &str) -> Option<usize> {
+        self.iter().position(|w| w == word)
+    }
+
+    /// The dice rolls that pick `word` from this list.
+    ///
+    /// The word is looked up as it is and in lowercase, so that capitalized
+    /// words of a passphrase are found too.
+    pub fn rolls_for_word(&self, word: &str) -> Result<Vec<u8>, Error> {
+        let num_dice = self.dice_per_word()?;
+
+        let index = self
+            .position(word)
+            .or_else(|| self.position(&word.to_lowercase()))
+            .ok_or_else(|| Error::UnknownWord(String::from(word)))?;
+        Ok(dice::rolls_for(index, num_dice))
+    }
+
+    /// Format this list the way the EFF's lists are, with every word preceded
+    /// by the dice rolls that pick it and a tab, one word per line.
+    ///
+    /// Fails unless the size of this list is a power of six.
+    pub fn to_dice_file(&self) -> Result<String, Error> {
+        let num_dice = self.dice_per_word()?;
+
+        let mut text =
+            String::with_capacity(self.len() * (num_dice as usize + self.max_size() + 2));
+        for (index, word) in self.iter().enumerate() {
+            let rolls = dice::format_rolls(&dice::rolls_for(index, num_dice));
+            text.push_str(&format!("{rolls}\t{word}\n"));
+        }
+
+        Ok(text)
+    }
+
+    /// The SHA-256 hash, in hex, of this list formatted by
+    /// [`Wordlist::to_dice_file`].
+    ///
+    /// Fails unless the size of this list is a power of six.
+    pub fn sha256(&self) -> Result<String, Error> {
+        let digest = Sha256::digest(self.to_dice_file()?);
+        Ok(digest.iter().map(|byte| format!("{byte:02x}")).collect())
+    }
+
+    /// Iterate over all words in this list, in order.
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        (0..self.len()).filter_map(move |index| self.get(index))
+    }
+}
+
+impl Default for Wordlist {
+    fn default() -> Self {
+        Self::large()
+    }
+}
+
+/// The built-in lists by name.
+static BUILTIN: &[Builtin] = &[
+    Builtin {
+        name: "eff-large",
+        words: &words::WORDS,
+        language: "en",
+        source: "https://www.eff.org/files/2016/07/18/eff_large_wordlist.txt",
+        license: "CC BY 3.0 US",
+        sha256: Some("addd35536511597a02fa0a9ff1e5284677b8883b83e986e43f15a3db996b903e"),
+    },
+    Builtin {
+        name: "eff-short",
+        words: &short_words::SHORT_WORDS,
+        language: "en",
+        source: "https://www.eff.org/files/2016/09/08/eff_short_wordlist_1.txt",
+        license: "CC BY 3.0 US",
+        // Not pinned until checked against a download from eff.org.
+        sha256: None,
+    },
+];
+
+struct Builtin {
+    name: &'static str,
+    words: &'static PackedWords,
+    language: &'static str,
+    source: &'static str,
+    license: &'static str,
+    /// The published hash of the upstream file, see
+    /// [`Wordlist::builtin_sha256`]. Never one computed from `words`, which
+    /// would verify nothing.
+    sha256: Option<&'static str>,
+}
+
+fn find_builtin(name: &str) -> Option<&'static Builtin> {
+    BUILTIN.iter().find(|builtin| builtin.name == name)
+}
+
+/// Read the file at `path`, naming it in any error.
+pub(crate) fn read_file(path: &Path) -> Result<String, Error> {
+    fs::read_to_string(path)
+        .map_err(|err| io::Error::new(err.kind(), format!("{}: {err}", path.display())).into())
+}
+
+/// The smallest number of leading characters that tells distinct `words`, in
+/// sorted order, apart.
+pub(crate) fn prefix_len(words: &[&str]) -> usize {
+    // A word shares its longest prefix with a neighbor in sorted order.
+    words
+        .windows(2)
+        .map(|pair| {
+            let common = pair[0]
+                .chars()
+                .zip(pair[1].chars())
+                .take_while(|(a, b)| a == b)
+                .count();
+            common + 1
+        })
+        .max()
+        .unwrap_or(1)
+}
+
+fn invalid(reason: String) -> Error {
+    Error::InvalidWordlist(reason)
+}
+
+fn is_dice_rolls(s: &str) -> bool {
+    s.bytes().all(|b| (b'1'..=b'6').contains(&b))
+}
+
+/// Read the words from the text of a wordlist file without validating them,
+/// see [`Wordlist::parse`].
+pub(crate) fn parse_words(text: &str) -> Result<Vec<&str>, Error> {
+    let lines = content_lines(text)
+        .into_iter()
+        .map(|(number, line)| (number, line.trim()))
+        .filter(|(_, line)| !line.is_empty());
+
+    let mut plain = Vec::new();
+    let mut indexed = Vec::new();
+    for (number, line) in lines {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        match fields[..] {
+            [word] => plain.push((number, word)),
+            [rolls, word] if is_dice_rolls(rolls) => indexed.push((number, rolls, word)),
+            _ => return Err(invalid(format!("line {number}: expected a single word"))),
+        }
+
+        if !plain.is_empty() && !indexed.is_empty() {
+            return Err(invalid(format!(
+                "line {number}: mixes plain and dice-indexed entries"
+            )));
+        }
+    }
+
+    let words = if indexed.is_empty() {
+        plain.into_iter().map(|(_, word)| word).collect()
+    } else {
+        sort_by_rolls(indexed)?
+    };
+
+    Ok(words)
+}
+
+/// The numbered lines of `text`, or of the message it signs if it's a PGP
+/// clear-signed message, like the `.asc` files Diceware lists come in.
+fn content_lines(text: &str) -> Vec<(usize, &str)> {
+    let lines: Vec<(usize, &str)> = text
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line))
+        .collect();
+
+    let first = lines.iter().position(|(_, line)| !line.trim().is_empty());
+    let Some(first) = first.filter(|&first| lines[first].1.trim() == PGP_MESSAGE) else {
+        return lines;
+    };
+
+    // Armor headers like "Hash: SHA256" end at the first blank line.
+    lines[first..]
+        .iter()
+        .skip_while(|(_, line)| !line.trim().is_empty())
+        .take_while(|(_, line)| line.trim() != PGP_SIGNATURE)
+        .map(|&(number, line)| (number, line.strip_prefix("- ").unwrap_or(line)))
+        .collect()
+}
+
+const PGP_MESSAGE: &str = "-----BEGIN PGP SIGNED MESSAGE-----";
+const PGP_SIGNATURE: &str = "-----BEGIN PGP SIGNATURE-----";
+
+/// Order dice-indexed entries by their rolls, making sure that every possible
+/// sequence of rolls appears exactly once.
+fn sort_by_rolls<'a>(mut entries: Vec<(usize, &str, &'a str)>) -> Result<Vec<&'a str>, Error> {
+    let num_dice = entries[0].1.len();
+    if let Some((number, _, _)) = entries.iter().find(|(_, rolls, _)| rolls.len() != num_dice) {
+        return Err(invalid(format!(
+            "line {number}: expected {num_dice} dice rolls"
+        )));
+    }
+
+    entries.sort_unstable_by_key(|(_, rolls, _)| *rolls);
+    for pair in entries.windows(2) {
+        if pair[0].1 == pair[1].1 {
+            let (number, rolls, _) = pair[1];
+            return Err(invalid(format!("line {number}: duplicate rolls {rolls}")));
+        }
+    }
+
+    let expected = u32::try_from(num_dice)
+        .ok()
+        .and_then(|num_dice| 6usize.checked_pow(num_dice));
+    if expected != Some(entries.len()) {
+        return Err(invalid(format!(
+            "{} entries, but {num_dice} dice call for 6^{num_dice}",
+            entries.len()
+        )));
+    }
+
+    Ok(entries.into_iter().map(|(_, _, word)| word).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(text: &str) -> String {
+        Wordlist::parse(text).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn rejects_mixed_plain_and_dice_indexed_lines() {
+        assert!(error("1\tfoo\nbar\n").contains("line 2: mixes plain and dice-indexed"));
+        assert!(error("foo\n1\tbar\n").contains("line 2: mixes plain and dice-indexed"));
+    }
+
+    #[test]
+    fn rejects_duplicate_rolls() {
+        let text = "1 a\n2 b\n3 c\n4 d\n5 e\n3 f\n";
+        assert!(error(text).contains("duplicate rolls 3"));
+    }
+
+    #[test]
+    fn rejects_inconsistent_roll_lengths() {
+        assert!(error("1 a\n22 b\n").contains("line 2: expected 1 dice rolls"));
+    }
+
+    #[test]
+    fn rejects_incomplete_sets_of_rolls() {
+        assert!(error("1 a\n2 b\n3 c\n").contains("3 entries, but 1 dice call for 6^1"));
+    }
+
+    #[test]
+    fn sorts_dice_indexed_lines_by_roll() {
+        let wordlist = Wordlist::parse("6 f\n1 a\n3 c\n\n2 b\n5 e\n4 d\n").unwrap();
+        assert_eq!(
+            wordlist.iter().collect::<Vec<_>>(),
+            ["a", "b", "c", "d", "e", "f"]
+        );
+        assert_eq!(wordlist.dice_per_word().unwrap(), 1);
+    }
+
+    #[test]
+    fn builtin_lists_know_their_largest_word() {
+        for name in Wordlist::builtin_names() {
+            let wordlist = Wordlist::builtin(name).unwrap();
+            let largest = wordlist.iter().map(str::len).max().unwrap();
+            assert_eq!(wordlist.max_size(), largest, "{name}");
+        }
+    }
+
+    #[test]
+    fn finds_rolls_for_capitalized_words() {
+        let wordlist = Wordlist::large();
+        assert_eq!(wordlist.rolls_for_word("Abacus").unwrap(), [1, 1, 1, 1, 1]);
+        assert_eq!(wordlist.rolls_for_word("ZOOM").unwrap(), [6, 6, 6, 6, 6]);
+        assert!(wordlist.rolls_for_word("Abacuses").is_err());
+    }
+
+    #[test]
+    fn reads_clear_signed_lists() {
+        let text = "\
+-----BEGIN PGP SIGNED MESSAGE-----
+Hash: SHA256
+
+1\ta
+2\tb
+3\tc
+4\td
+5\te
+6\t!
+-----BEGIN PGP SIGNATURE-----
+
+iQEzBAEBCAAdFiEE
+-----END PGP SIGNATURE-----
+";
+        let wordlist = Wordlist::parse(text).unwrap();
+        assert_eq!(
+            wordlist.iter().collect::<Vec<_>>(),
+            ["a", "b", "c", "d", "e", "!"]
+        );
+
+        // Lines starting with a dash are escaped with another one.
+        let text = "-----BEGIN PGP SIGNED MESSAGE-----\nHash: SHA256\n\nish\n- -ish\n\
+                    -----BEGIN PGP SIGNATURE-----\n";
+        let wordlist = Wordlist::parse(text).unwrap();
+        assert_eq!(wordlist.iter().collect::<Vec<_>>(), ["ish", "-ish"]);
+        assert!(error("x\n-----BEGIN PGP SIGNATURE-----\n").contains("line 2"));
+    }
+
+    #[test]
+    fn dice_files_round_trip() {
+        let words: Vec<String> = (0..36).map(|i| format!("word{i}")).collect();
+        let wordlist = Wordlist::parse(&words.join("\n")).unwrap();
+
+        let dice_file = wordlist.to_dice_file().unwrap();
+        assert!(dice_file.starts_with("11\tword0\n12\tword1\n"));
+        let parsed = Wordlist::parse(&dice_file).unwrap();
+        assert!(parsed.iter().eq(wordlist.iter()));
+        assert_eq!(parsed.sha256().unwrap(), wordlist.sha256().unwrap());
+    }
+}
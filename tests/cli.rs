@@ -0,0 +1,62 @@
+use std::process::{Command, Output};
+
+/// Run spiceware with `args`.
+fn spiceware(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_spiceware"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+/// The hex digits of the seed b"spiceware", used by tests/seed.rs too.
+const SEED: &str = "737069636577617265";
+
+#[test]
+fn seeded_passphrases_are_stable() {
+    let output = spiceware(&["--seed", SEED, "-q", "-n", "2"]);
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "fringe tint coastland thickness\nnext undrilled breeder satchel\n"
+    );
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .starts_with("warning: passphrases from --seed can be reproduced"));
+
+    let output = spiceware(&[
+        "--seed",
+        SEED,
+        "-q",
+        "-l",
+        "eff-short",
+        "-w",
+        "5",
+        "--delimiter-set",
+        "-_.",
+        "-c",
+        "random-word",
+        "--digits",
+        "2",
+        "--symbols",
+        "1",
+    ]);
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "cedar_ditch-tasty-crawl_Crook33%\n"
+    );
+}
+
+#[test]
+fn rejects_invalid_seeds() {
+    for (seed, reason) in [
+        ("zz", "'z' is not a hex digit"),
+        ("abc", "must be an even number of hex digits"),
+        ("", "must be an even number of hex digits"),
+    ] {
+        let output = spiceware(&["--seed", seed]);
+        assert!(!output.status.success());
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(stderr.contains(reason), "{seed:?}: {stderr}");
+    }
+}
@@ -0,0 +1,21 @@
+use spiceware::Wordlist;
+
+#[test]
+fn builtin_lists_match_upstream() {
+    for name in Wordlist::builtin_names() {
+        let Some(expected) = Wordlist::builtin_sha256(name) else {
+            continue;
+        };
+        let wordlist = Wordlist::builtin(name).unwrap();
+        assert_eq!(wordlist.sha256().unwrap(), expected, "{name}");
+    }
+}
+
+#[test]
+#[ignore = "eff-short has no pinned upstream hash yet"]
+fn builtin_lists_have_pinned_hashes() {
+    let unpinned: Vec<&str> = Wordlist::builtin_names()
+        .filter(|name| Wordlist::builtin_sha256(name).is_none())
+        .collect();
+    assert!(unpinned.is_empty(), "no upstream hash for {unpinned:?}");
+}
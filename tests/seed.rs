@@ -0,0 +1,28 @@
+use spiceware::{source, Case, Generator, Wordlist};
+
+#[test]
+fn seeded_passphrases_are_stable() {
+    let mut generator = Generator::new(Wordlist::large()).rng(source::seeded(b"spiceware"));
+    let passphrases: Vec<String> = (0..2)
+        .map(|_| generator.generate().unwrap().into_string())
+        .collect();
+    assert_eq!(
+        passphrases,
+        [
+            "fringe tint coastland thickness",
+            "next undrilled breeder satchel"
+        ]
+    );
+
+    let mut generator = Generator::new(Wordlist::short())
+        .num_words(5)
+        .delimiter_set("-_.")
+        .case(Case::RandomWord)
+        .digits(2)
+        .symbols(1)
+        .rng(source::seeded(b"spiceware"));
+    assert_eq!(
+        generator.generate().unwrap().as_str(),
+        "cedar_ditch-tasty-crawl_Crook33%"
+    );
+}